@@ -0,0 +1,152 @@
+use displaydoc::Display;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use std::{fs, io, path::Path};
+use thiserror::Error;
+
+use crate::Part;
+
+#[derive(Debug, Error, Display)]
+pub enum Errors {
+    /// Could not access the answers file
+    Io(#[from] io::Error),
+    /// Could not parse the answers file
+    Parse(#[from] toml::de::Error),
+    /// Could not serialize the answers
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// Known correct answer for a day, part and input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Answer {
+    pub day: u8,
+    pub part: u8,
+    /// [`fingerprint`] of the input the answer is for
+    pub input: String,
+    pub answer: String,
+}
+
+/// Registry of known correct answers, stored as TOML
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Answers {
+    #[serde(default, rename = "answer")]
+    answers: Vec<Answer>,
+}
+
+/// Outcome of checking a produced answer against the registry
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The answer matches the recorded one
+    Match,
+    /// The answer differs from the recorded one
+    Mismatch { expected: String },
+    /// No answer is recorded for this input
+    New,
+}
+
+impl Answers {
+    /// Load the registry, a missing file is an empty registry
+    pub fn load(path: &Path) -> Result<Self, Errors> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(toml::from_str(&content)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Save the registry sorted by day, part and input so that it diffs cleanly
+    pub fn save(&mut self, path: &Path) -> Result<(), Errors> {
+        self.answers
+            .sort_by(|a, b| (a.day, a.part, &a.input).cmp(&(b.day, b.part, &b.input)));
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Recorded answer for a day and part on the input with the given fingerprint
+    pub fn get(&self, day: u8, part: Part, input: &str) -> Option<&str> {
+        self.answers
+            .iter()
+            .find(|a| a.day == day && a.part == part.number() && a.input == input)
+            .map(|a| a.answer.as_str())
+    }
+
+    /// Record an answer, replacing any previous answer for the same day, part and input
+    pub fn insert(&mut self, day: u8, part: Part, input: &str, answer: &str) {
+        let answer = Answer {
+            day,
+            part: part.number(),
+            input: input.to_string(),
+            answer: answer.to_string(),
+        };
+
+        match self
+            .answers
+            .iter_mut()
+            .find(|a| a.day == answer.day && a.part == answer.part && a.input == answer.input)
+        {
+            Some(existing) => *existing = answer,
+            None => self.answers.push(answer),
+        }
+    }
+
+    /// Compare a produced answer with the recorded one
+    pub fn check(&self, day: u8, part: Part, input: &str, answer: &str) -> Verdict {
+        match self.get(day, part, input) {
+            Some(expected) if expected == answer => Verdict::Match,
+            Some(expected) => Verdict::Mismatch {
+                expected: expected.to_string(),
+            },
+            None => Verdict::New,
+        }
+    }
+}
+
+/// Short stable identifier of a puzzle input, line endings and trailing whitespace are ignored
+pub fn fingerprint(input: &str) -> String {
+    let normalized = input.replace("\r\n", "\n");
+    let digest = Sha256::digest(normalized.trim_end().as_bytes());
+
+    digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fingerprint, Answers, Verdict};
+    use crate::Part;
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(fingerprint("1\n2\n3"), fingerprint("1\r\n2\r\n3\n"));
+        assert_ne!(fingerprint("1\n2\n3"), fingerprint("1\n2\n4"));
+        assert_eq!(fingerprint("").len(), 16);
+    }
+
+    #[test]
+    fn test_check() -> Result<(), super::Errors> {
+        let mut answers: Answers = toml::from_str(
+            r#"
+[[answer]]
+day = 1
+part = 1
+input = "abc"
+answer = "42"
+"#,
+        )?;
+
+        assert_eq!(answers.check(1, Part::One, "abc", "42"), Verdict::Match);
+        assert_eq!(
+            answers.check(1, Part::One, "abc", "43"),
+            Verdict::Mismatch {
+                expected: "42".to_string()
+            }
+        );
+        assert_eq!(answers.check(1, Part::Two, "abc", "42"), Verdict::New);
+        assert_eq!(answers.check(1, Part::One, "def", "42"), Verdict::New);
+
+        answers.insert(1, Part::One, "abc", "43");
+        answers.insert(1, Part::Two, "abc", "7");
+        assert_eq!(answers.get(1, Part::One, "abc"), Some("43"));
+        assert_eq!(answers.get(1, Part::Two, "abc"), Some("7"));
+        Ok(())
+    }
+}
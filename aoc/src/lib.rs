@@ -0,0 +1,364 @@
+use cpu_time::ThreadTime;
+use displaydoc::Display;
+use rayon::prelude::*;
+use std::{
+    fmt, fs,
+    io::{self, Read as _},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+use utils::{Diagnostic, Solution};
+
+pub mod answers;
+pub mod fetch;
+pub mod parallel;
+pub mod record;
+mod registry;
+pub mod scaffold;
+pub mod submit;
+
+pub use record::{Record, Value};
+pub use registry::{day, days, Day};
+
+#[derive(Debug, Error, Display)]
+pub enum Errors {
+    /// "{0}" is not a valid part, expected 1 or 2
+    InvalidPart(String),
+    /// Could not read input {0}
+    UnreadableInput(String, #[source] io::Error),
+}
+
+/// One of the two challenges of a day
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    /// The part as it is numbered in the puzzle
+    pub fn number(self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => f.pad("1"),
+            Part::Two => f.pad("2"),
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = Errors;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ => Err(Errors::InvalidPart(s.to_string())),
+        }
+    }
+}
+
+/// Wall clock and CPU time spent in a phase, the CPU time only counts the thread running it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub wall: Duration,
+    pub cpu: Duration,
+}
+
+impl Timing {
+    /// Run `f` and measure how long it took
+    pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Self) {
+        let wall = Instant::now();
+        let cpu = ThreadTime::now();
+        let result = f();
+        let timing = Timing {
+            wall: wall.elapsed(),
+            cpu: cpu.elapsed(),
+        };
+
+        (result, timing)
+    }
+}
+
+impl std::ops::Add for Timing {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Timing {
+            wall: self.wall + other.wall,
+            cpu: self.cpu + other.cpu,
+        }
+    }
+}
+
+/// Answer to one part along with the time it took to solve
+pub struct PartReport {
+    pub part: Part,
+    pub answer: Result<Value, utils::Error>,
+    pub solve_time: Timing,
+}
+
+/// Result of running a day on an input, the parsing is shared by all of the parts
+pub struct Report {
+    pub parse_time: Timing,
+    pub parts: Vec<PartReport>,
+}
+
+/// Type erased [`Solution`] so that every day can be driven the same way
+pub trait Puzzle: Sync {
+    /// Parse the input once then solve each of the requested parts, fails only if parsing fails
+    fn run(&self, input: &str, parts: &[Part]) -> Result<Report, utils::Error>;
+
+    /// Like [`Puzzle::run`] but the parts are solved concurrently on the rayon pool
+    fn run_concurrently(&self, input: &str, parts: &[Part]) -> Result<Report, utils::Error>;
+}
+
+impl<S> Puzzle for S
+where
+    S: Solution + Sync,
+    for<'i> S::Input<'i>: Sync,
+    S::Answer1: Into<Value>,
+    S::Answer2: Into<Value>,
+{
+    fn run(&self, input: &str, parts: &[Part]) -> Result<Report, utils::Error> {
+        let (parsed, parse_time) = parse(self, input);
+        let parsed = parsed?;
+        let parts = parts
+            .iter()
+            .map(|&part| solve(self, &parsed, part))
+            .collect();
+
+        Ok(Report { parse_time, parts })
+    }
+
+    fn run_concurrently(&self, input: &str, parts: &[Part]) -> Result<Report, utils::Error> {
+        let (parsed, parse_time) = parse(self, input);
+        let parsed = parsed?;
+        // The pool's threads do not inherit the current span
+        let span = tracing::Span::current();
+        let parts = parts
+            .par_iter()
+            .map(|&part| span.in_scope(|| solve(self, &parsed, part)))
+            .collect();
+
+        Ok(Report { parse_time, parts })
+    }
+}
+
+/// Parse the input in its own span, timing only the parsing
+fn parse<'i, S: Solution>(
+    solution: &S,
+    input: &'i str,
+) -> (Result<S::Input<'i>, utils::Error>, Timing) {
+    let _span = tracing::info_span!("parse", len = input.len()).entered();
+    Timing::measure(|| solution.parse(input))
+}
+
+fn solve<S>(solution: &S, parsed: &S::Input<'_>, part: Part) -> PartReport
+where
+    S: Solution,
+    S::Answer1: Into<Value>,
+    S::Answer2: Into<Value>,
+{
+    let _span = tracing::info_span!("solve", part = part.number()).entered();
+    let (answer, solve_time) = Timing::measure(|| match part {
+        Part::One => solution.part1(parsed).map(Into::into),
+        Part::Two => solution.part2(parsed).map(Into::into),
+    });
+
+    PartReport {
+        part,
+        answer,
+        solve_time,
+    }
+}
+
+/// Default location of a day's puzzle input relative to the workspace root
+pub fn default_input_path(root: &Path, day: u8) -> PathBuf {
+    root.join(format!("day{}", day))
+        .join("src")
+        .join("input.txt")
+}
+
+/// Read the puzzle input from a file, or from stdin if the path is `-`
+pub fn read_input(path: &Path) -> Result<String, Errors> {
+    let mut input = String::new();
+    if path == Path::new("-") {
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| Errors::UnreadableInput(path.display().to_string(), e))?;
+    } else {
+        input = fs::read_to_string(path)
+            .map_err(|e| Errors::UnreadableInput(path.display().to_string(), e))?;
+    }
+
+    Ok(input)
+}
+
+/// First [`Diagnostic`] in the chain of errors, to point at where the input is malformed
+pub fn diagnostic<'e>(error: &'e (dyn std::error::Error + 'static)) -> Option<&'e Diagnostic> {
+    std::iter::successors(Some(error), |error| error.source())
+        .find_map(|error| error.downcast_ref::<Diagnostic>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Part, Puzzle as _};
+
+    #[test]
+    fn test_run() -> Result<(), utils::Error> {
+        let report = day1::Day1.run("1721\n979\n366\n299\n675\n1456", &Part::ALL)?;
+        let answers: Vec<super::Value> = report
+            .parts
+            .into_iter()
+            .map(|part| part.answer)
+            .collect::<Result<_, _>>()?;
+
+        assert_eq!(
+            answers,
+            vec![
+                super::Value::Integer(514579),
+                super::Value::Integer(241861950)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_records() -> Result<(), utils::Error> {
+        let report = day12::Day12.run("F10\nN3\nF7\nR90\nF11", &Part::ALL)?;
+        let lines: Vec<String> = super::Record::from_report(12, &report)
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<_, _>>()?;
+
+        let record: serde_json::Value = serde_json::from_str(&lines[0])?;
+        assert_eq!((&record["day"], &record["part"]), (&12.into(), &1.into()));
+        assert!(record["parse_time_ns"].is_u64() && record["error"].is_null());
+        assert_eq!(record["answer"].as_f64(), Some(25.0));
+        assert!(lines[1].contains(r#""answer":286.0"#));
+
+        let value = 0.1 + 0.2;
+        let encoded = serde_json::to_string(&super::Value::Float(value))?;
+        assert_eq!(
+            serde_json::from_str::<f64>(&encoded)?.to_bits(),
+            value.to_bits()
+        );
+        assert_eq!(
+            serde_json::to_string(&super::Value::Float(f64::NAN))?,
+            r#""NaN""#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_failure_record() -> Result<(), serde_json::Error> {
+        let error = match day1::Day1.run("1721\n97x", &Part::ALL) {
+            Ok(_) => panic!("Malformed input was parsed"),
+            Err(error) => error,
+        };
+        let record = super::Record::parse_failure(1, Part::Two, &*error);
+
+        assert_eq!(
+            serde_json::to_string(&record)?,
+            r#"{"day":1,"part":2,"answer":null,"parse_time_ns":null,"solve_time_ns":null,"error":["Line 2, column 1: Parse error invalid digit found in string","invalid digit found in string"]}"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagnostic() {
+        let error = match day1::Day1.run("1721\n97x", &Part::ALL) {
+            Ok(_) => panic!("Malformed input was parsed"),
+            Err(error) => error,
+        };
+        let diagnostic = super::diagnostic(&*error).expect("Parse errors carry a diagnostic");
+
+        assert_eq!(
+            diagnostic.render("input.txt"),
+            "error: Parse error invalid digit found in string
+ --> input.txt:2:1
+  |
+2 | 97x
+  | ^^^
+"
+        );
+    }
+
+    #[test]
+    fn test_parallel() {
+        let days: Vec<super::Day> = [12, 1, 5].iter().filter_map(|&n| super::day(n)).collect();
+        let reports = super::parallel::run_days(&days, &Part::ALL, |day| match day {
+            12 => Ok("F10\nN3\nF7\nR90\nF11".to_string()),
+            1 => Ok("1721\n979\n366\n299\n675\n1456".to_string()),
+            _ => Err("No input".into()),
+        });
+        let order: Vec<u8> = reports.iter().map(|report| report.day).collect();
+        assert_eq!(order, vec![12, 1, 5]);
+
+        let report = super::parallel::TimingReport::new(&reports, Default::default(), 2);
+        assert_eq!(report.failures(), 1);
+
+        let text = report.to_string();
+        let rows: Vec<(&str, &str, &str)> = text
+            .lines()
+            .skip(1)
+            .take(7)
+            .map(|line| {
+                (
+                    &line[..3],
+                    &line[4..9],
+                    line[10..].split(' ').next().unwrap_or(""),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("  1", "parse", ""),
+                ("  1", "    1", "514579"),
+                ("  1", "    2", "241861950"),
+                ("  5", "parse", "error:"),
+                (" 12", "parse", ""),
+                (" 12", "    1", "25"),
+                (" 12", "    2", "286"),
+            ]
+        );
+        assert_eq!(
+            text.lines().skip_while(|line| *line != "Slowest").count(),
+            3
+        );
+
+        assert_eq!(
+            report.without_timings().to_string(),
+            "Day Phase Answer
+  1 parse
+  1     1 514579
+  1     2 241861950
+  5 parse error: No input
+ 12 parse
+ 12     1 25
+ 12     2 286
+"
+        );
+    }
+
+    #[test]
+    fn test_registry() {
+        let numbers: Vec<u8> = super::days().iter().map(|day| day.number).collect();
+        assert_eq!(numbers, (1..=19).collect::<Vec<u8>>());
+        assert!(super::day(20).is_none());
+    }
+}
@@ -0,0 +1,688 @@
+use anyhow::{bail, Context as _, Result};
+use aoc::{
+    answers::{self, Answers, Verdict},
+    fetch::{self, Origin},
+    parallel::{self, DayReport, TimingReport},
+    submit, Day, Part, Record, Report,
+};
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Instant,
+};
+use structopt::StructOpt;
+use tracing_flame::{FlameLayer, FlushGuard};
+use tracing_subscriber::{prelude::*, EnvFilter};
+
+#[derive(StructOpt)]
+#[structopt(about = "Advent of Code 2020 solutions")]
+enum Command {
+    /// Run a single day, or every registered day with `all`
+    Run(RunArgs),
+    /// Check every day's answers against the recorded answers
+    Verify(VerifyArgs),
+    /// Create a new day crate from the template and add it to the workspace
+    New(NewArgs),
+    /// Download a day's input, or read it from the cache
+    Fetch(FetchArgs),
+    /// Submit a day's answer, unless it was already submitted
+    Submit(SubmitArgs),
+    /// Check every line of the day 2 password database against a policy and say why lines fail
+    Audit(AuditArgs),
+}
+
+#[derive(StructOpt)]
+struct RunArgs {
+    /// Day number to run, or `all`
+    day: Selection,
+    /// Only run this part (1 or 2)
+    #[structopt(short, long)]
+    part: Option<Part>,
+    /// Input file, `-` reads from stdin [default: <root>/dayN/src/input.txt]
+    #[structopt(short, long, parse(from_os_str))]
+    input: Option<PathBuf>,
+    /// Workspace root used to locate the default inputs
+    #[structopt(long, default_value = ".", parse(from_os_str))]
+    root: PathBuf,
+    /// Print one JSON record per part (JSON Lines) instead of text
+    #[structopt(long)]
+    json: bool,
+    /// Run the days and their parts concurrently then print a timing report
+    #[structopt(long)]
+    parallel: bool,
+    /// Number of phases listed as the slowest in the timing report
+    #[structopt(long, default_value = "5")]
+    slowest: usize,
+    /// Leave the timings out of the report so that it is the same from one run to the next
+    #[structopt(long)]
+    no_timings: bool,
+    /// Write the spans to this file as folded stacks, to render with `inferno-flamegraph`
+    #[structopt(long, parse(from_os_str))]
+    flame: Option<PathBuf>,
+    /// Print the tracing events enabled by these directives to stderr, like `debug` or `day11=debug`
+    #[structopt(long, env = "AOC_LOG")]
+    log: Option<String>,
+}
+
+#[derive(StructOpt)]
+struct VerifyArgs {
+    /// Workspace root used to locate the inputs
+    #[structopt(long, default_value = ".", parse(from_os_str))]
+    root: PathBuf,
+    /// Answers registry [default: <root>/answers.toml]
+    #[structopt(long, parse(from_os_str))]
+    answers: Option<PathBuf>,
+    /// Record the new answers in the registry
+    #[structopt(long)]
+    record: bool,
+}
+
+#[derive(StructOpt)]
+struct NewArgs {
+    /// Day number of the new crate
+    day: u8,
+    /// Workspace root to create the crate in
+    #[structopt(long, default_value = ".", parse(from_os_str))]
+    root: PathBuf,
+}
+
+#[derive(StructOpt)]
+struct ServerArgs {
+    /// Puzzle year
+    #[structopt(long, default_value = "2020")]
+    year: u16,
+    /// Session cookie of the logged in account [default: the content of <config dir>/aoc/session]
+    #[structopt(long, env = "AOC_SESSION", hide_env_values = true)]
+    session: Option<String>,
+    /// Server of the puzzles
+    #[structopt(long, env = "AOC_BASE_URL", default_value = "https://adventofcode.com")]
+    base_url: String,
+    /// Cache of the inputs and submissions [default: <cache dir>/aoc]
+    #[structopt(long, parse(from_os_str))]
+    cache_dir: Option<PathBuf>,
+    /// HTTP proxy like `http://proxy:3128`
+    #[structopt(long, env = "AOC_PROXY")]
+    proxy: Option<String>,
+}
+
+#[derive(StructOpt)]
+struct FetchArgs {
+    /// Day number to fetch
+    day: u8,
+    #[structopt(flatten)]
+    server: ServerArgs,
+    /// Ask the server whether the cached input changed
+    #[structopt(long)]
+    refresh: bool,
+    /// Where to write the input, `-` prints it [default: <root>/dayN/src/input.txt]
+    #[structopt(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+    /// Workspace root used to locate the default output
+    #[structopt(long, default_value = ".", parse(from_os_str))]
+    root: PathBuf,
+}
+
+#[derive(StructOpt)]
+struct SubmitArgs {
+    /// Day number of the answer
+    day: u8,
+    /// Part of the answer (1 or 2)
+    part: Part,
+    /// Answer to submit [default: the answer computed from the input]
+    #[structopt(short, long)]
+    answer: Option<String>,
+    #[structopt(flatten)]
+    server: ServerArgs,
+    /// Input file the answer is computed from [default: <root>/dayN/src/input.txt]
+    #[structopt(short, long, parse(from_os_str))]
+    input: Option<PathBuf>,
+    /// Workspace root used to locate the default input
+    #[structopt(long, default_value = ".", parse(from_os_str))]
+    root: PathBuf,
+}
+
+#[derive(StructOpt)]
+struct AuditArgs {
+    /// Policy of the part to check against, 1 for the sled rental one and 2 for the toboggan one
+    #[structopt(short, long, default_value = "1")]
+    part: Part,
+    /// Input file, `-` reads from stdin [default: <root>/day2/src/input.txt]
+    #[structopt(short, long, parse(from_os_str))]
+    input: Option<PathBuf>,
+    /// Workspace root used to locate the default input
+    #[structopt(long, default_value = ".", parse(from_os_str))]
+    root: PathBuf,
+    /// Print one JSON record per line (JSON Lines) instead of a table
+    #[structopt(long)]
+    json: bool,
+    /// Leave the valid lines out
+    #[structopt(long)]
+    failures: bool,
+}
+
+enum Selection {
+    All,
+    Day(u8),
+}
+
+impl FromStr for Selection {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Selection::All),
+            _ => s.parse().map(Selection::Day),
+        }
+    }
+}
+
+impl RunArgs {
+    fn parts(&self) -> Vec<Part> {
+        self.part
+            .map_or_else(|| Part::ALL.to_vec(), |part| vec![part])
+    }
+
+    fn input_for(&self, day: u8) -> PathBuf {
+        self.input
+            .clone()
+            .unwrap_or_else(|| aoc::default_input_path(&self.root, day))
+    }
+
+    fn days(&self) -> Result<Vec<Day>> {
+        match self.day {
+            Selection::All if self.input.is_some() => {
+                bail!("--input can only be used when running a single day")
+            }
+            Selection::All => Ok(aoc::days()),
+            Selection::Day(number) => {
+                Ok(vec![aoc::day(number).with_context(|| {
+                    format!("Day {} is not solved", number)
+                })?])
+            }
+        }
+    }
+}
+
+/// Install the subscriber requested by the tracing options, the flame graph is only complete once
+/// the returned guard is dropped
+fn init_tracing(args: &RunArgs) -> Result<Option<FlushGuard<BufWriter<File>>>> {
+    if args.flame.is_none() && args.log.is_none() {
+        return Ok(None);
+    }
+
+    let log = match &args.log {
+        Some(directives) => Some(
+            tracing_subscriber::fmt::layer()
+                .with_writer(io::stderr)
+                .with_filter(EnvFilter::try_new(directives)?),
+        ),
+        None => None,
+    };
+    let (flame, guard) = match &args.flame {
+        Some(path) => {
+            let (layer, guard) = FlameLayer::with_file(path)
+                .with_context(|| format!("Creating {}", path.display()))?;
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+    tracing_subscriber::registry().with(log).with(flame).init();
+
+    Ok(guard)
+}
+
+fn run_day(day: &Day, args: &RunArgs) -> Result<Report> {
+    let _span = tracing::info_span!("day", day = day.number).entered();
+    let path = args.input_for(day.number);
+    let input = aoc::read_input(&path)?;
+    day.puzzle
+        .run(&input, &args.parts())
+        .map_err(|e| {
+            if let Some(diagnostic) = aoc::diagnostic(&*e) {
+                eprint!("{}", diagnostic.render(&path.display().to_string()));
+            }
+            anyhow::Error::from_boxed(e)
+        })
+        .with_context(|| format!("Parsing input for day {}", day.number))
+}
+
+fn run_one(number: u8, args: &RunArgs) -> Result<()> {
+    let day = aoc::day(number).with_context(|| format!("Day {} is not solved", number))?;
+    let report = run_day(&day, args)?;
+
+    eprintln!("Parsed in {:.2?}", report.parse_time.wall);
+    for part in report.parts {
+        let number = part.part;
+        let answer = part
+            .answer
+            .map_err(anyhow::Error::from_boxed)
+            .with_context(|| format!("Solving part {}", number))?;
+
+        println!("Challenge {}: {}", number, answer);
+        eprintln!("Solved in {:.2?}", part.solve_time.wall);
+    }
+
+    Ok(())
+}
+
+fn run_all(args: &RunArgs) -> Result<()> {
+    if args.input.is_some() {
+        bail!("--input can only be used when running a single day");
+    }
+
+    let mut failures = 0;
+    println!(
+        "{:>3} {:>4} {:<20} {:>10} {:>10}",
+        "Day", "Part", "Answer", "Parse", "Solve"
+    );
+    for day in aoc::days() {
+        let report = match run_day(&day, args) {
+            Ok(report) => report,
+            Err(e) => {
+                failures += 1;
+                println!("{:>3} {:>4} error: {:#}", day.number, "-", e);
+                continue;
+            }
+        };
+
+        for part in report.parts {
+            let answer = part.answer.unwrap_or_else(|e| {
+                failures += 1;
+                format!("error: {}", e).into()
+            });
+
+            println!(
+                "{:>3} {:>4} {:<20} {:>10.2?} {:>10.2?}",
+                day.number, part.part, answer, report.parse_time.wall, part.solve_time.wall
+            );
+        }
+    }
+
+    if failures > 0 {
+        bail!("{} run(s) failed", failures);
+    }
+
+    Ok(())
+}
+
+/// Records for every requested part of a day, a parse failure is reported in each of them
+fn records(
+    day: u8,
+    report: Result<&Report, &(dyn Error + 'static)>,
+    parts: &[Part],
+) -> Vec<Record> {
+    match report {
+        Ok(report) => Record::from_report(day, report),
+        Err(e) => parts
+            .iter()
+            .map(|&part| Record::parse_failure(day, part, e))
+            .collect(),
+    }
+}
+
+fn print_records(records: Vec<Record>) -> Result<usize> {
+    let mut failures = 0;
+    for record in records {
+        if record.error.is_some() {
+            failures += 1;
+        }
+        println!("{}", serde_json::to_string(&record)?);
+    }
+
+    Ok(failures)
+}
+
+fn run_json(args: &RunArgs) -> Result<()> {
+    let mut failures = 0;
+    for day in args.days()? {
+        let report = run_day(&day, args);
+        let report = report
+            .as_ref()
+            .map_err(|e| -> &(dyn Error + 'static) { e.as_ref() });
+        failures += print_records(records(day.number, report, &args.parts()))?;
+    }
+
+    if failures > 0 {
+        bail!("{} run(s) failed", failures);
+    }
+
+    Ok(())
+}
+
+fn run_parallel(args: &RunArgs) -> Result<()> {
+    let days = args.days()?;
+    let start = Instant::now();
+    let reports = parallel::run_days(&days, &args.parts(), |day| {
+        Ok(aoc::read_input(&args.input_for(day))?)
+    });
+    let elapsed = start.elapsed();
+
+    let failures = if args.json {
+        let mut failures = 0;
+        for DayReport { day, report } in &reports {
+            let report = report
+                .as_ref()
+                .map_err(|e| -> &(dyn Error + 'static) { &**e });
+            failures += print_records(records(*day, report, &args.parts()))?;
+        }
+        failures
+    } else {
+        let mut report = TimingReport::new(&reports, elapsed, args.slowest);
+        if args.no_timings {
+            report = report.without_timings();
+        }
+        print!("{}", report);
+        report.failures()
+    };
+
+    if failures > 0 {
+        bail!("{} run(s) failed", failures);
+    }
+
+    Ok(())
+}
+
+fn verify(args: &VerifyArgs) -> Result<()> {
+    let answers_path = args
+        .answers
+        .clone()
+        .unwrap_or_else(|| args.root.join("answers.toml"));
+    let mut answers = Answers::load(&answers_path)
+        .with_context(|| format!("Loading {}", answers_path.display()))?;
+
+    let (mut matched, mut mismatched, mut new, mut missing, mut failed) = (0, 0, 0, 0, 0);
+    for day in aoc::days() {
+        let path = aoc::default_input_path(&args.root, day.number);
+        if !path.exists() {
+            missing += 1;
+            println!(
+                "{:>3} {:>4} missing input {}",
+                day.number,
+                "-",
+                path.display()
+            );
+            continue;
+        }
+
+        let input = aoc::read_input(&path)?;
+        let fingerprint = answers::fingerprint(&input);
+        let report = match day.puzzle.run(&input, &Part::ALL) {
+            Ok(report) => report,
+            Err(e) => {
+                failed += 1;
+                println!("{:>3} {:>4} error: {}", day.number, "-", e);
+                continue;
+            }
+        };
+
+        for part in report.parts {
+            let answer = match part.answer {
+                Ok(answer) => answer.to_string(),
+                Err(e) => {
+                    failed += 1;
+                    println!("{:>3} {:>4} error: {}", day.number, part.part, e);
+                    continue;
+                }
+            };
+
+            match answers.check(day.number, part.part, &fingerprint, &answer) {
+                Verdict::Match => {
+                    matched += 1;
+                    println!("{:>3} {:>4} ok {}", day.number, part.part, answer);
+                }
+                Verdict::Mismatch { expected } => {
+                    mismatched += 1;
+                    println!(
+                        "{:>3} {:>4} MISMATCH expected {} got {}",
+                        day.number, part.part, expected, answer
+                    );
+                }
+                Verdict::New => {
+                    new += 1;
+                    println!("{:>3} {:>4} new {}", day.number, part.part, answer);
+                    if args.record {
+                        answers.insert(day.number, part.part, &fingerprint, &answer);
+                    }
+                }
+            }
+        }
+    }
+
+    println!(
+        "{} matched, {} mismatched, {} new, {} missing, {} failed",
+        matched, mismatched, new, missing, failed
+    );
+
+    if args.record && new > 0 {
+        answers
+            .save(&answers_path)
+            .with_context(|| format!("Saving {}", answers_path.display()))?;
+        println!(
+            "Recorded {} new answer(s) in {}",
+            new,
+            answers_path.display()
+        );
+    }
+
+    if mismatched + failed > 0 {
+        bail!("{} mismatched and {} failed", mismatched, failed);
+    }
+
+    Ok(())
+}
+
+fn new_day(args: &NewArgs) -> Result<()> {
+    let directory = aoc::scaffold::new_day(&args.root, args.day)
+        .with_context(|| format!("Creating day {}", args.day))?;
+
+    println!("Created {}", directory.display());
+    println!(
+        "Paste the input in src/input.txt, then add day{} to aoc/Cargo.toml and to the registry to run it",
+        args.day
+    );
+    Ok(())
+}
+
+impl ServerArgs {
+    fn client(&self) -> Result<fetch::Client> {
+        let session = match &self.session {
+            Some(session) => session.clone(),
+            None => {
+                let path = fetch::session_file().context("No configuration directory")?;
+                fs::read_to_string(&path)
+                    .with_context(|| {
+                        format!(
+                            "No session token, pass --session, set AOC_SESSION or write it to {}",
+                            path.display()
+                        )
+                    })?
+                    .trim()
+                    .to_string()
+            }
+        };
+        let cache_dir = match &self.cache_dir {
+            Some(cache_dir) => cache_dir.clone(),
+            None => fetch::default_cache_dir()?,
+        };
+
+        Ok(fetch::Client::new(fetch::Config {
+            base_url: self.base_url.clone(),
+            year: self.year,
+            session,
+            cache_dir,
+            proxy: self.proxy.clone(),
+        })?)
+    }
+}
+
+fn fetch(args: &FetchArgs) -> Result<()> {
+    let client = args.server.client()?;
+    let fetched = client
+        .input(args.day, args.refresh)
+        .with_context(|| format!("Fetching the input of day {}", args.day))?;
+    let origin = match fetched.origin {
+        Origin::Cache => "Read from the cache",
+        Origin::Downloaded => "Downloaded",
+        Origin::NotModified => "Unchanged since the last download",
+    };
+    eprintln!("{} ({})", origin, client.cache_path(args.day).display());
+
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| aoc::default_input_path(&args.root, args.day));
+    if output == Path::new("-") {
+        print!("{}", fetched.input);
+    } else {
+        fs::write(&output, &fetched.input)
+            .with_context(|| format!("Writing {}", output.display()))?;
+        eprintln!("Wrote {}", output.display());
+    }
+
+    Ok(())
+}
+
+fn submit(args: &SubmitArgs) -> Result<()> {
+    let answer = match &args.answer {
+        Some(answer) => answer.clone(),
+        None => {
+            let day = aoc::day(args.day)
+                .with_context(|| format!("Day {} is not solved, pass --answer", args.day))?;
+            let path = args
+                .input
+                .clone()
+                .unwrap_or_else(|| aoc::default_input_path(&args.root, args.day));
+            let input = aoc::read_input(&path)?;
+            let report = day
+                .puzzle
+                .run(&input, &[args.part])
+                .map_err(anyhow::Error::from_boxed)
+                .with_context(|| format!("Parsing input for day {}", args.day))?;
+            report
+                .parts
+                .into_iter()
+                .next()
+                .context("No answer computed")?
+                .answer
+                .map_err(anyhow::Error::from_boxed)
+                .with_context(|| format!("Solving part {}", args.part))?
+                .to_string()
+        }
+    };
+
+    let verdict = args
+        .server
+        .client()?
+        .submit(args.day, args.part, &answer)
+        .with_context(|| {
+            format!(
+                "Submitting {} for day {} part {}",
+                answer, args.day, args.part
+            )
+        })?;
+    println!("{} is {}", answer, verdict);
+
+    if verdict != submit::Verdict::Correct {
+        bail!("The answer was not accepted");
+    }
+
+    Ok(())
+}
+
+fn audit(args: &AuditArgs) -> Result<()> {
+    let path = args
+        .input
+        .clone()
+        .unwrap_or_else(|| aoc::default_input_path(&args.root, 2));
+    let input = aoc::read_input(&path)?;
+    let reports = match args.part {
+        Part::One => day2::report(&input, &day2::SledRentalPolicy),
+        Part::Two => day2::report(&input, &day2::TobogganPolicy),
+    };
+
+    let (mut valid, mut invalid, mut malformed, mut unchecked) = (0, 0, 0, 0);
+    if !args.json {
+        println!(
+            "{:>5} {:<9} {:<24} {:<9} Reason",
+            "Line", "Rule", "Password", "Verdict"
+        );
+    }
+    for report in &reports {
+        match report.verdict {
+            day2::Verdict::Valid => valid += 1,
+            day2::Verdict::Invalid { .. } => invalid += 1,
+            day2::Verdict::Malformed { .. } => malformed += 1,
+            day2::Verdict::Unchecked { .. } => unchecked += 1,
+        }
+        if args.failures && report.verdict == day2::Verdict::Valid {
+            continue;
+        }
+
+        if args.json {
+            println!("{}", serde_json::to_string(report)?);
+            continue;
+        }
+        let (rule, password) = match &report.entry {
+            Some(entry) => (
+                format!("{}-{} {}", entry.a, entry.b, entry.target),
+                entry.password,
+            ),
+            None => (String::new(), ""),
+        };
+        let (verdict, reason) = match &report.verdict {
+            day2::Verdict::Valid => ("valid", String::new()),
+            day2::Verdict::Invalid { reason } => ("invalid", reason.to_string()),
+            day2::Verdict::Malformed { column, error } => {
+                ("malformed", format!("column {}: {}", column, error))
+            }
+            day2::Verdict::Unchecked { column, error } => {
+                ("unchecked", format!("column {}: {}", column, error))
+            }
+        };
+        println!(
+            "{:>5} {:<9} {:<24} {:<9} {}",
+            report.line, rule, password, verdict, reason
+        );
+    }
+    eprintln!(
+        "{} valid, {} invalid, {} malformed, {} unchecked",
+        valid, invalid, malformed, unchecked
+    );
+
+    if malformed > 0 || unchecked > 0 {
+        bail!(
+            "{} malformed and {} unchecked line(s) in {}",
+            malformed,
+            unchecked,
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    match Command::from_args() {
+        Command::Run(args) => {
+            let _guard = init_tracing(&args)?;
+            if args.parallel {
+                run_parallel(&args)
+            } else if args.json {
+                run_json(&args)
+            } else {
+                match args.day {
+                    Selection::All => run_all(&args),
+                    Selection::Day(number) => run_one(number, &args),
+                }
+            }
+        }
+        Command::Verify(args) => verify(&args),
+        Command::New(args) => new_day(&args),
+        Command::Fetch(args) => fetch(&args),
+        Command::Submit(args) => submit(&args),
+        Command::Audit(args) => audit(&args),
+    }
+}
@@ -0,0 +1,196 @@
+use rayon::prelude::*;
+use std::{cmp::Reverse, fmt, time::Duration};
+
+use crate::{Day, Part, Report, Timing};
+
+/// Outcome of running one day, failing if its input could not be read or parsed
+pub struct DayReport {
+    pub day: u8,
+    pub report: Result<Report, utils::Error>,
+}
+
+/// Run every day at once on the rayon pool, each day parses its input then solves its parts
+/// concurrently
+///
+/// The reports come back in the order of `days` whatever order they finished in.
+pub fn run_days<F>(days: &[Day], parts: &[Part], input: F) -> Vec<DayReport>
+where
+    F: Fn(u8) -> Result<String, utils::Error> + Sync,
+{
+    days.par_iter()
+        .map(|day| {
+            let _span = tracing::info_span!("day", day = day.number).entered();
+            DayReport {
+                day: day.number,
+                report: input(day.number)
+                    .and_then(|input| day.puzzle.run_concurrently(&input, parts)),
+            }
+        })
+        .collect()
+}
+
+/// Step of a day's run
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    Parse,
+    Solve(Part),
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Parse => f.pad("parse"),
+            Phase::Solve(part) => part.fmt(f),
+        }
+    }
+}
+
+/// Timing of a single phase along with its answer, or the error it failed with
+struct PhaseRow {
+    day: u8,
+    phase: Phase,
+    outcome: Result<String, String>,
+    timing: Option<Timing>,
+}
+
+impl PhaseRow {
+    fn outcome(&self) -> String {
+        match &self.outcome {
+            Ok(answer) => answer.clone(),
+            Err(e) => format!("error: {}", e),
+        }
+    }
+}
+
+/// Per phase timings of a parallel run, sorted by day then phase so that runs line up
+///
+/// The slowest phases are ranked by wall clock time, ties are broken by day and phase. Only the
+/// order of the rows is the same from one run to the next, the timings and the ranking vary:
+/// [`TimingReport::without_timings`] leaves them out of a report meant to be diffed.
+pub struct TimingReport {
+    rows: Vec<PhaseRow>,
+    elapsed: Duration,
+    slowest: usize,
+    timings: bool,
+}
+
+impl TimingReport {
+    /// Report on `reports`, `elapsed` is the wall clock time of the whole run and `slowest` the
+    /// number of phases to rank
+    pub fn new(reports: &[DayReport], elapsed: Duration, slowest: usize) -> Self {
+        let mut rows = Vec::new();
+        for DayReport { day, report } in reports {
+            let day = *day;
+            let report = match report {
+                Ok(report) => report,
+                Err(e) => {
+                    rows.push(PhaseRow {
+                        day,
+                        phase: Phase::Parse,
+                        outcome: Err(e.to_string()),
+                        timing: None,
+                    });
+                    continue;
+                }
+            };
+
+            rows.push(PhaseRow {
+                day,
+                phase: Phase::Parse,
+                outcome: Ok(String::new()),
+                timing: Some(report.parse_time),
+            });
+            for part in &report.parts {
+                rows.push(PhaseRow {
+                    day,
+                    phase: Phase::Solve(part.part),
+                    outcome: part
+                        .answer
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .map_err(ToString::to_string),
+                    timing: Some(part.solve_time),
+                });
+            }
+        }
+        rows.sort_by_key(|row| (row.day, row.phase));
+
+        Self {
+            rows,
+            elapsed,
+            slowest,
+            timings: true,
+        }
+    }
+
+    /// List the answers of the phases only, without their timings, the total or the slowest
+    pub fn without_timings(mut self) -> Self {
+        self.timings = false;
+        self
+    }
+
+    /// Number of phases that failed
+    pub fn failures(&self) -> usize {
+        self.rows.iter().filter(|row| row.outcome.is_err()).count()
+    }
+
+    /// Sum of the timings of every phase
+    pub fn total(&self) -> Timing {
+        self.rows
+            .iter()
+            .filter_map(|row| row.timing)
+            .fold(Timing::default(), |total, timing| total + timing)
+    }
+}
+
+impl fmt::Display for TimingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.timings {
+            writeln!(f, "{:>3} {:>5} Answer", "Day", "Phase")?;
+            for row in &self.rows {
+                let line = format!("{:>3} {:>5} {}", row.day, row.phase, row.outcome());
+                writeln!(f, "{}", line.trim_end())?;
+            }
+            return Ok(());
+        }
+
+        let line = |f: &mut fmt::Formatter<'_>, row: &PhaseRow| match row.timing {
+            Some(timing) => writeln!(
+                f,
+                "{:>3} {:>5} {:<20} {:>10.2?} {:>10.2?}",
+                row.day,
+                row.phase,
+                row.outcome(),
+                timing.wall,
+                timing.cpu
+            ),
+            None => writeln!(f, "{:>3} {:>5} {}", row.day, row.phase, row.outcome()),
+        };
+
+        writeln!(
+            f,
+            "{:>3} {:>5} {:<20} {:>10} {:>10}",
+            "Day", "Phase", "Answer", "Wall", "CPU"
+        )?;
+        for row in &self.rows {
+            line(f, row)?;
+        }
+
+        let total = self.total();
+        writeln!(
+            f,
+            "{:<30} {:>10.2?} {:>10.2?}",
+            "Total", total.wall, total.cpu
+        )?;
+        writeln!(f, "{:<30} {:>10.2?}", "Elapsed", self.elapsed)?;
+
+        let mut slowest: Vec<&PhaseRow> = self.rows.iter().filter(|r| r.timing.is_some()).collect();
+        slowest.sort_by_key(|row| (Reverse(row.timing.map(|t| t.wall)), row.day, row.phase));
+        writeln!(f, "\nSlowest")?;
+        for row in slowest.into_iter().take(self.slowest) {
+            line(f, row)?;
+        }
+
+        Ok(())
+    }
+}
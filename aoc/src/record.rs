@@ -0,0 +1,153 @@
+use serde::{Serialize, Serializer};
+use std::{convert::TryFrom, error::Error, fmt};
+
+use crate::{Part, Report};
+
+/// Answer to a part, keeping the type the solution produced it as
+///
+/// Serializes to a JSON number for integers and finite floats, floats are written in their
+/// shortest form that parses back to the exact same `f64`. Non finite floats have no JSON number
+/// so they are written as the strings `"NaN"`, `"inf"` and `"-inf"`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(i128),
+    Float(f64),
+    Text(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Integer(value) => value.fmt(f),
+            Value::Float(value) => value.fmt(f),
+            Value::Text(value) => value.fmt(f),
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Value::Integer(value) => serializer.serialize_i128(value),
+            Value::Float(value) if value.is_finite() => serializer.serialize_f64(value),
+            Value::Float(value) if value.is_nan() => serializer.serialize_str("NaN"),
+            Value::Float(value) if value > 0.0 => serializer.serialize_str("inf"),
+            Value::Float(_) => serializer.serialize_str("-inf"),
+            Value::Text(ref value) => serializer.serialize_str(value),
+        }
+    }
+}
+
+macro_rules! integer_values {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for Value {
+                fn from(value: $integer) -> Self {
+                    Value::Integer(i128::from(value))
+                }
+            }
+        )*
+    };
+}
+
+integer_values!(i8, i16, i32, i64, i128, u8, u16, u32, u64);
+
+impl From<isize> for Value {
+    fn from(value: isize) -> Self {
+        // isize is at most 64 bits wide on every supported platform
+        Value::Integer(value as i128)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Value::Integer(value as i128)
+    }
+}
+
+impl From<u128> for Value {
+    fn from(value: u128) -> Self {
+        i128::try_from(value).map_or_else(|_| Value::Text(value.to_string()), Value::Integer)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Float(f64::from(value))
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+/// Machine readable result of one part of a day, `aoc run --json` prints one per line
+///
+/// Times are in nanoseconds. When parsing fails every requested part gets a record with the
+/// parse error, no answer and no times.
+#[derive(Debug, Serialize)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub answer: Option<Value>,
+    pub parse_time_ns: Option<u64>,
+    pub solve_time_ns: Option<u64>,
+    /// Messages of the error and each of its sources, outermost first
+    pub error: Option<Vec<String>>,
+}
+
+impl Record {
+    /// Records for every part of a report
+    pub fn from_report(day: u8, report: &Report) -> Vec<Self> {
+        report
+            .parts
+            .iter()
+            .map(|part| {
+                let (answer, error) = match &part.answer {
+                    Ok(answer) => (Some(answer.clone()), None),
+                    Err(error) => (None, Some(error_chain(&**error))),
+                };
+
+                Record {
+                    day,
+                    part: part.part.number(),
+                    answer,
+                    parse_time_ns: Some(nanoseconds(report.parse_time.wall)),
+                    solve_time_ns: Some(nanoseconds(part.solve_time.wall)),
+                    error,
+                }
+            })
+            .collect()
+    }
+
+    /// Record for a part that could not run because the input did not parse
+    pub fn parse_failure(day: u8, part: Part, error: &(dyn Error + 'static)) -> Self {
+        Record {
+            day,
+            part: part.number(),
+            answer: None,
+            parse_time_ns: None,
+            solve_time_ns: None,
+            error: Some(error_chain(error)),
+        }
+    }
+}
+
+fn nanoseconds(duration: std::time::Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+/// Message of the error followed by the messages of each of its sources
+pub fn error_chain(error: &(dyn Error + 'static)) -> Vec<String> {
+    std::iter::successors(Some(error), |&error| error.source())
+        .map(ToString::to_string)
+        .collect()
+}
@@ -0,0 +1,46 @@
+use crate::Puzzle;
+
+/// A registered day along with the solution that solves it
+pub struct Day {
+    pub number: u8,
+    pub puzzle: Box<dyn Puzzle>,
+}
+
+impl Day {
+    fn new(number: u8, puzzle: impl Puzzle + 'static) -> Self {
+        Self {
+            number,
+            puzzle: Box::new(puzzle),
+        }
+    }
+}
+
+/// Every solved day in order
+pub fn days() -> Vec<Day> {
+    vec![
+        Day::new(1, day1::Day1),
+        Day::new(2, day2::Day2),
+        Day::new(3, day3::Day3),
+        Day::new(4, day4::Day4),
+        Day::new(5, day5::Day5),
+        Day::new(6, day6::Day6),
+        Day::new(7, day7::Day7),
+        Day::new(8, day8::Day8),
+        Day::new(9, day9::Day9::default()),
+        Day::new(10, day10::Day10),
+        Day::new(11, day11::Day11),
+        Day::new(12, day12::Day12),
+        Day::new(13, day13::Day13),
+        Day::new(14, day14::Day14),
+        Day::new(15, day15::Day15::default()),
+        Day::new(16, day16::Day16::default()),
+        Day::new(17, day17::Day17::default()),
+        Day::new(18, day18::Day18),
+        Day::new(19, day19::Day19),
+    ]
+}
+
+/// Find a registered day by its number
+pub fn day(number: u8) -> Option<Day> {
+    days().into_iter().find(|day| day.number == number)
+}
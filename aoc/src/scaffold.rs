@@ -0,0 +1,157 @@
+use displaydoc::Display;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+const CARGO_TEMPLATE: &str = include_str!("../templates/Cargo.toml.template");
+const LIB_TEMPLATE: &str = include_str!("../templates/lib.rs.template");
+
+#[derive(Debug, Error, Display)]
+pub enum Errors {
+    /// Could not write the new day: {0}
+    Io(#[from] io::Error),
+    /// Could not parse the workspace manifest: {0}
+    Manifest(#[from] toml::de::Error),
+    /// The workspace manifest has no `members` list
+    NoMembers,
+    /// Day {0} is not an Advent of Code day, expected 1 to 25
+    InvalidDay(u8),
+    /// {0} already exists
+    AlreadyExists(String),
+}
+
+/// Create the `dayN` crate under `root` from the template and add it to the workspace members
+///
+/// The crate has an `Errors` enum, a [`Solution`](utils::Solution) skeleton, ignored example tests
+/// and an empty `src/input.txt` to paste the input in. Returns the directory of the new crate.
+pub fn new_day(root: &Path, day: u8) -> Result<PathBuf, Errors> {
+    if !(1..=25).contains(&day) {
+        return Err(Errors::InvalidDay(day));
+    }
+
+    let name = format!("day{}", day);
+    let directory = root.join(&name);
+    if directory.exists() {
+        return Err(Errors::AlreadyExists(directory.display().to_string()));
+    }
+
+    // Check the manifest before creating anything
+    let manifest_path = root.join("Cargo.toml");
+    let manifest = register_member(&fs::read_to_string(&manifest_path)?, &name)?;
+
+    let source = directory.join("src");
+    fs::create_dir_all(&source)?;
+    let day = day.to_string();
+    fs::write(
+        directory.join("Cargo.toml"),
+        CARGO_TEMPLATE.replace("{day}", &day),
+    )?;
+    fs::write(source.join("lib.rs"), LIB_TEMPLATE.replace("{day}", &day))?;
+    fs::write(source.join("input.txt"), "")?;
+    fs::write(manifest_path, manifest)?;
+
+    Ok(directory)
+}
+
+/// Add `member` to the workspace members of `manifest`
+///
+/// The list is rewritten one member per line, the other members first in their current order then
+/// the days by number, so the result does not depend on the order the days were added in. The rest
+/// of the manifest is left untouched.
+///
+/// # Examples
+///
+/// ```
+/// let manifest = "[workspace]\n\nmembers = [\"utils\", \"day10\", \"day9\"]\n";
+/// let updated = aoc::scaffold::register_member(manifest, "day2").unwrap();
+/// assert_eq!(
+///     updated,
+///     "[workspace]\n\nmembers = [\n    \"utils\",\n    \"day2\",\n    \"day9\",\n    \"day10\",\n]\n"
+/// );
+/// assert_eq!(aoc::scaffold::register_member(&updated, "day2").unwrap(), updated);
+/// ```
+pub fn register_member(manifest: &str, member: &str) -> Result<String, Errors> {
+    let parsed: toml::Value = manifest.parse()?;
+    let mut members: Vec<&str> = parsed
+        .get("workspace")
+        .and_then(|workspace| workspace.get("members"))
+        .and_then(toml::Value::as_array)
+        .ok_or(Errors::NoMembers)?
+        .iter()
+        .filter_map(toml::Value::as_str)
+        .collect();
+    if !members.contains(&member) {
+        members.push(member);
+    }
+    // Stable sort, only the days move
+    members.sort_by_key(|&member| day_number(member));
+
+    let (start, end) = members_span(manifest).ok_or(Errors::NoMembers)?;
+    let list: String = members
+        .iter()
+        .map(|member| format!("    {:?},\n", member))
+        .collect();
+
+    Ok(format!(
+        "{}members = [\n{}]{}",
+        &manifest[..start],
+        list,
+        &manifest[end..]
+    ))
+}
+
+/// Day of a `dayN` member
+fn day_number(member: &str) -> Option<u8> {
+    member.strip_prefix("day")?.parse().ok()
+}
+
+/// Byte range of the `members = [...]` array in the manifest, the members never contain brackets
+fn members_span(manifest: &str) -> Option<(usize, usize)> {
+    let workspace = manifest.find("[workspace]")?;
+    let start = manifest[workspace..]
+        .match_indices("members")
+        .map(|(index, key)| (workspace + index, key.len()))
+        .find(|&(index, len)| {
+            let value = manifest[index + len..].trim_start_matches([' ', '\t']);
+            manifest[..index].ends_with('\n') && value.starts_with('=')
+        })?
+        .0;
+    let end = start + manifest[start..].find(']')? + 1;
+
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{new_day, Errors};
+    use std::fs;
+
+    #[test]
+    fn test_new_day() -> Result<(), Errors> {
+        let root = std::env::temp_dir().join(format!("aoc-new-day-{}", std::process::id()));
+        fs::create_dir_all(&root)?;
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"utils\", \"day1\"]\n",
+        )?;
+
+        let directory = new_day(&root, 20)?;
+        let lib = fs::read_to_string(directory.join("src").join("lib.rs"))?;
+        assert!(lib.contains("impl Solution for Day20 {"));
+        let manifest = fs::read_to_string(directory.join("Cargo.toml"))?;
+        assert!(manifest.contains(r#"name = "day20""#));
+        assert!(directory.join("src").join("input.txt").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("Cargo.toml"))?,
+            "[workspace]\nmembers = [\n    \"utils\",\n    \"day1\",\n    \"day20\",\n]\n"
+        );
+
+        assert!(matches!(new_day(&root, 20), Err(Errors::AlreadyExists(_))));
+        assert!(matches!(new_day(&root, 26), Err(Errors::InvalidDay(26))));
+
+        fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+use displaydoc::Display;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+use crate::{
+    fetch::{self, Client},
+    Part,
+};
+
+#[derive(Debug, Error, Display)]
+pub enum Errors {
+    /// {0}
+    Server(#[from] fetch::Errors),
+    /// Could not access the submission history: {0}
+    Io(#[from] io::Error),
+    /// Could not read the submission history: {0}
+    History(#[from] toml::de::Error),
+    /// Could not write the submission history: {0}
+    SaveHistory(#[from] toml::ser::Error),
+    /// {answer} was already submitted for day {day} part {part}, it is {verdict}
+    Duplicate {
+        day: u8,
+        part: u8,
+        answer: String,
+        verdict: Verdict,
+    },
+    /// Day {day} part {part} is already solved, the answer is {answer}
+    AlreadySolved { day: u8, part: u8, answer: String },
+    /// An answer was submitted too recently, wait {0}s before submitting another one
+    Cooldown(u64),
+    /// Could not find the verdict in the answer page: {0}
+    UnknownVerdict(String),
+}
+
+/// Verdict of the server on a submitted answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize, Deserialize)]
+pub enum Verdict {
+    /// the right answer
+    Correct,
+    /// too high
+    TooHigh,
+    /// too low
+    TooLow,
+    /// not the right answer
+    Incorrect,
+    /// not checked, an answer was submitted too recently and there is {0:?} left to wait
+    Wait(Duration),
+    /// not checked, the part is either already solved or still locked
+    WrongLevel,
+}
+
+impl Verdict {
+    /// Whether the server checked the answer, only checked answers are kept in the history
+    pub fn is_checked(self) -> bool {
+        !matches!(self, Verdict::Wait(_) | Verdict::WrongLevel)
+    }
+}
+
+/// Answer previously submitted and its verdict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Attempt {
+    day: u8,
+    part: u8,
+    answer: String,
+    verdict: Verdict,
+}
+
+/// Submissions of an account for a year, stored as TOML next to its inputs
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct History {
+    /// Seconds since the Unix epoch until which the server refuses new answers
+    cooldown_until: Option<u64>,
+    #[serde(default, rename = "attempt")]
+    attempts: Vec<Attempt>,
+}
+
+impl Client {
+    /// Submit the answer to a part, unless the history shows it would be pointless
+    ///
+    /// Answers already checked are never submitted again, and nothing is submitted while the
+    /// server is still refusing answers after the previous one.
+    pub fn submit(&self, day: u8, part: Part, answer: &str) -> Result<Verdict, Errors> {
+        let path = self.history_path();
+        let mut history: History = match fetch::read_if_exists(&path)? {
+            Some(content) => toml::from_str(&content)?,
+            None => History::default(),
+        };
+
+        let attempts = history
+            .attempts
+            .iter()
+            .filter(|attempt| (attempt.day, attempt.part) == (day, part.number()));
+        for attempt in attempts {
+            if attempt.answer == answer {
+                return Err(Errors::Duplicate {
+                    day,
+                    part: part.number(),
+                    answer: answer.to_string(),
+                    verdict: attempt.verdict,
+                });
+            }
+            if attempt.verdict == Verdict::Correct {
+                return Err(Errors::AlreadySolved {
+                    day,
+                    part: part.number(),
+                    answer: attempt.answer.clone(),
+                });
+            }
+        }
+
+        let now = unix_time();
+        if let Some(until) = history.cooldown_until.filter(|&until| until > now) {
+            return Err(Errors::Cooldown(until - now));
+        }
+
+        let level = part.number().to_string();
+        let request = self.request("POST", day, "answer");
+        let page = fetch::response(
+            day,
+            request.send_form(&[("level", &level), ("answer", answer)]),
+        )?
+        .into_string()?;
+        let (verdict, cooldown) = parse_verdict(&page)?;
+
+        history.cooldown_until = cooldown.map(|cooldown| now + cooldown.as_secs());
+        if verdict.is_checked() {
+            history.attempts.push(Attempt {
+                day,
+                part: part.number(),
+                answer: answer.to_string(),
+                verdict,
+            });
+        }
+        fs::create_dir_all(self.account_dir())?;
+        fs::write(path, toml::to_string(&history)?)?;
+
+        Ok(verdict)
+    }
+
+    fn history_path(&self) -> PathBuf {
+        self.account_dir().join("submissions.toml")
+    }
+}
+
+/// Verdict of the answer page and how long the server refuses new answers after it
+///
+/// Only the text of the page's article is looked at, wrong answers come with the time to wait
+/// before the next one like `please wait 5 minutes` and early answers with the time left like
+/// `You have 4m 23s left to wait`.
+pub fn parse_verdict(page: &str) -> Result<(Verdict, Option<Duration>), Errors> {
+    let text = article_text(page);
+
+    let verdict = if text.contains("That's the right answer") {
+        Verdict::Correct
+    } else if text.contains("You gave an answer too recently") {
+        let left = text
+            .split("You have ")
+            .nth(1)
+            .and_then(|rest| rest.split(" left to wait").next())
+            .and_then(parse_duration)
+            .unwrap_or_default();
+        return Ok((Verdict::Wait(left), Some(left)));
+    } else if text.contains("not the right answer") {
+        if text.contains("too high") {
+            Verdict::TooHigh
+        } else if text.contains("too low") {
+            Verdict::TooLow
+        } else {
+            Verdict::Incorrect
+        }
+    } else if text.contains("solving the right level") {
+        Verdict::WrongLevel
+    } else {
+        return Err(Errors::UnknownVerdict(text));
+    };
+
+    let cooldown = text
+        .split("please wait ")
+        .nth(1)
+        .and_then(|rest| rest.split(" before trying again").next())
+        .and_then(parse_duration);
+    Ok((verdict, cooldown))
+}
+
+/// Text of the `<article>` of the page, or of the whole page, without the markup
+fn article_text(page: &str) -> String {
+    let article = page
+        .split("<article>")
+        .nth(1)
+        .and_then(|rest| rest.split("</article>").next())
+        .unwrap_or(page);
+
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in article.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Durations like `one minute`, `5 minutes` or `4m 23s`, `None` if the text is not one or if it
+/// does not fit in 64 bits of seconds
+fn parse_duration(text: &str) -> Option<Duration> {
+    let mut words = text.split_whitespace();
+    let mut seconds: u64 = 0;
+    while let Some(word) = words.next() {
+        let (amount, unit) = match word.find(|c: char| !c.is_ascii_digit()) {
+            Some(0) if word == "one" || word == "a" => (1, words.next()?),
+            Some(0) => return None,
+            Some(index) => (word[..index].parse().ok()?, &word[index..]),
+            None => (word.parse().ok()?, words.next()?),
+        };
+        let unit = match unit.trim_end_matches(',') {
+            "h" | "hour" | "hours" => 3600,
+            "m" | "minute" | "minutes" => 60,
+            "s" | "second" | "seconds" => 1,
+            _ => return None,
+        };
+        seconds = seconds.checked_add(u64::checked_mul(amount, unit)?)?;
+    }
+
+    (seconds > 0).then(|| Duration::from_secs(seconds))
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_verdict, Verdict};
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_verdict() -> Result<(), super::Errors> {
+        let page = |text| format!("<main>\n<article><p>{}</p></article>\n</main>", text);
+        let cases = [
+            (
+                "That's the right answer!  You are <span class=\"day-success\">one gold star</span> closer.",
+                Verdict::Correct,
+                None,
+            ),
+            (
+                "That's not the right answer; your answer is too high.  If you're stuck, make sure you're using the full input data; please wait one minute before trying again. [<a href=\"/2020/day/1\">Return to Day 1</a>]",
+                Verdict::TooHigh,
+                Some(60),
+            ),
+            (
+                "That's not the right answer; your answer is too low.  please wait 5 minutes before trying again.",
+                Verdict::TooLow,
+                Some(300),
+            ),
+            (
+                "That's not the right answer.  please wait one minute before trying again.",
+                Verdict::Incorrect,
+                Some(60),
+            ),
+            (
+                "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 4m 23s left to wait.",
+                Verdict::Wait(Duration::from_secs(263)),
+                Some(263),
+            ),
+            (
+                "You don't seem to be solving the right level.  Did you already complete it?",
+                Verdict::WrongLevel,
+                None,
+            ),
+        ];
+
+        for (text, verdict, cooldown) in cases.iter() {
+            let expected = (*verdict, cooldown.map(Duration::from_secs));
+            assert_eq!(parse_verdict(&page(text))?, expected);
+        }
+        assert!(matches!(
+            parse_verdict("<article><p>Maintenance</p></article>"),
+            Err(super::Errors::UnknownVerdict(text)) if text == "Maintenance"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        use super::parse_duration;
+
+        assert_eq!(parse_duration("1h 2m 3s"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_duration("a minute"), Some(Duration::from_secs(60)));
+        assert_eq!(parse_duration("5 fortnights"), None);
+        assert_eq!(parse_duration(&format!("{}h", u64::MAX / 3600 + 1)), None);
+        assert_eq!(parse_duration(&format!("{}s 1s", u64::MAX)), None);
+    }
+}
@@ -0,0 +1,121 @@
+//! Stub of the puzzle server listening on a local port
+
+use std::{
+    error::Error,
+    io::{BufRead as _, BufReader, Read as _, Write as _},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+/// Request line, headers and body of a request received by the stub
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub line: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Status, extra headers and body of a stub response
+pub type Response = (u16, Vec<(&'static str, &'static str)>, String);
+
+/// HTTP server answering one request per connection, keeping every request it received
+pub struct Stub {
+    pub address: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl Stub {
+    pub fn start(respond: fn(&Request) -> Response) -> Result<Self, Box<dyn Error>> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?.to_string();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let received = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Some(request) = read_request(&stream) {
+                    let response = respond(&request);
+                    if let Ok(mut received) = received.lock() {
+                        received.push(request);
+                    }
+                    write_response(stream, response);
+                }
+            }
+        });
+
+        Ok(Self { address, requests })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests
+            .lock()
+            .map(|requests| requests.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut lines = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        lines.push(line.to_string());
+    }
+
+    let mut lines = lines.into_iter();
+    let line = lines.next()?;
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|header| {
+            let (name, value) = header.split_once(':')?;
+            Some((name.to_string(), value.trim().to_string()))
+        })
+        .collect();
+    let mut request = Request {
+        line,
+        headers,
+        body: String::new(),
+    };
+
+    let length = request
+        .header("Content-Length")
+        .unwrap_or("0")
+        .parse()
+        .ok()?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+    request.body = String::from_utf8(body).ok()?;
+    Some(request)
+}
+
+fn write_response(mut stream: TcpStream, (status, headers, body): Response) {
+    let mut response = format!(
+        "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    response.push_str(&body);
+    let _ = stream.write_all(response.as_bytes());
+}
@@ -0,0 +1,111 @@
+//! Input fetching against a local stub of the puzzle server
+
+mod common;
+
+use aoc::fetch::{Client, Config, Errors, Origin};
+use common::{Request, Response, Stub};
+use std::{error::Error, path::PathBuf};
+
+/// The puzzle server as seen by the fetcher: day 25 is still locked, the `expired` session is
+/// logged out and the input of every other day is at version `"v1"`
+fn puzzle_server(request: &Request) -> Response {
+    const INPUT: &str = "1721\n979\n366\n";
+
+    if request.header("Cookie") == Some("session=expired") {
+        (
+            400,
+            vec![],
+            "Puzzle inputs differ by user.  Please log in to get your puzzle input.".to_string(),
+        )
+    } else if request.line.contains("/day/25/") {
+        (
+            404,
+            vec![],
+            "Please don't repeatedly request this endpoint before it unlocks!".to_string(),
+        )
+    } else if request.header("If-None-Match") == Some("\"v1\"") {
+        (304, vec![], String::new())
+    } else {
+        (200, vec![("ETag", "\"v1\"")], INPUT.to_string())
+    }
+}
+
+fn config(stub: &Stub, session: &str, cache: &str) -> Config {
+    let cache_dir: PathBuf =
+        std::env::temp_dir().join(format!("aoc-fetch-{}-{}", cache, std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+
+    Config {
+        base_url: stub.url(),
+        year: 2020,
+        session: session.to_string(),
+        cache_dir,
+        proxy: None,
+    }
+}
+
+#[test]
+fn test_cached_input() -> Result<(), Box<dyn Error>> {
+    let stub = Stub::start(puzzle_server)?;
+    let client = Client::new(config(&stub, "token", "cached"))?;
+
+    let fetched = client.input(1, false)?;
+    assert_eq!(fetched.origin, Origin::Downloaded);
+    assert_eq!(fetched.input, "1721\n979\n366\n");
+    let path = client.cache_path(1);
+    assert_eq!(std::fs::read_to_string(&path)?, fetched.input);
+    assert!(path.ends_with("day1.txt") && !path.to_string_lossy().contains("token"));
+
+    assert_eq!(client.input(1, false)?.origin, Origin::Cache);
+    assert_eq!(stub.requests().len(), 1);
+
+    let revalidated = client.input(1, true)?;
+    assert_eq!(revalidated.origin, Origin::NotModified);
+    assert_eq!(revalidated.input, fetched.input);
+
+    let requests = stub.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].line, "GET /2020/day/1/input HTTP/1.1");
+    assert_eq!(requests[0].header("Cookie"), Some("session=token"));
+    assert_eq!(requests[0].header("If-None-Match"), None);
+    assert_eq!(requests[1].header("If-None-Match"), Some("\"v1\""));
+    Ok(())
+}
+
+#[test]
+fn test_rejected_requests() -> Result<(), Box<dyn Error>> {
+    let stub = Stub::start(puzzle_server)?;
+
+    let client = Client::new(config(&stub, "expired", "expired"))?;
+    assert!(matches!(
+        client.input(1, false),
+        Err(Errors::SessionExpired)
+    ));
+    assert!(!client.cache_path(1).exists());
+
+    let client = Client::new(config(&stub, "token", "locked"))?;
+    assert!(matches!(
+        client.input(25, false),
+        Err(Errors::NotAvailable(25))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_proxy() -> Result<(), Box<dyn Error>> {
+    let stub = Stub::start(puzzle_server)?;
+    let mut config = config(&stub, "token", "proxy");
+    config.proxy = Some(config.base_url.clone());
+    config.base_url = "http://adventofcode.invalid".to_string();
+
+    assert_eq!(
+        Client::new(config)?.input(2, false)?.origin,
+        Origin::Downloaded
+    );
+    let requests = stub.requests();
+    assert_eq!(
+        requests[0].line,
+        "GET http://adventofcode.invalid/2020/day/2/input HTTP/1.1"
+    );
+    Ok(())
+}
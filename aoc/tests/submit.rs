@@ -0,0 +1,120 @@
+//! Answer submission against a local stub of the puzzle server
+
+mod common;
+
+use aoc::{
+    fetch::{Client, Config},
+    submit::{Errors, Verdict},
+    Part,
+};
+use common::{Request, Response, Stub};
+use std::{error::Error, time::Duration};
+
+/// The puzzle server answering with the page of a different verdict for each answer
+fn puzzle_server(request: &Request) -> Response {
+    let answer = request.body.split("answer=").nth(1).unwrap_or("");
+    let text = match answer {
+        "514579" => "That's the right answer!  You are <span class=\"day-success\">one gold star</span> closer to saving your vacation.",
+        "9999" => "That's not the right answer; your answer is too high.  If you're stuck, make sure you're using the full input data; please wait one minute before trying again. [<a href=\"/2020/day/1\">Return to Day 1</a>]",
+        "1" => "That's not the right answer; your answer is too low.  please wait 5 minutes before trying again.",
+        "42" => "That's not the right answer.  please wait one minute before trying again.",
+        "7" => "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 4m 23s left to wait. [<a href=\"/2020/day/1\">Return to Day 1</a>]",
+        _ => "You don't seem to be solving the right level.  Did you already complete it? [<a href=\"/2020/day/1\">Return to Day 1</a>]",
+    };
+    let page = format!(
+        "<!DOCTYPE html>\n<html><body><main>\n<article><p>{}</p></article>\n</main></body></html>",
+        text
+    );
+
+    (200, vec![("Content-Type", "text/html")], page)
+}
+
+/// Client with its own empty history
+fn empty_client(stub: &Stub, history: &str) -> Result<Client, Box<dyn Error>> {
+    let cache_dir =
+        std::env::temp_dir().join(format!("aoc-submit-{}-{}", history, std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+
+    Ok(Client::new(Config {
+        base_url: stub.url(),
+        year: 2020,
+        session: "token".to_string(),
+        cache_dir,
+        proxy: None,
+    })?)
+}
+
+#[test]
+fn test_history() -> Result<(), Box<dyn Error>> {
+    let stub = Stub::start(puzzle_server)?;
+    let client = empty_client(&stub, "history")?;
+
+    assert_eq!(client.submit(1, Part::One, "514579")?, Verdict::Correct);
+    assert!(matches!(
+        client.submit(1, Part::One, "514579"),
+        Err(Errors::Duplicate {
+            verdict: Verdict::Correct,
+            ..
+        })
+    ));
+    assert!(matches!(
+        client.submit(1, Part::One, "42"),
+        Err(Errors::AlreadySolved { answer, .. }) if answer == "514579"
+    ));
+
+    assert_eq!(client.submit(1, Part::Two, "9999")?, Verdict::TooHigh);
+    match client.submit(1, Part::Two, "1") {
+        Err(Errors::Cooldown(seconds)) => assert!(seconds > 0 && seconds <= 60),
+        other => panic!("Submitted during the cooldown: {:?}", other),
+    }
+
+    let requests = stub.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].line, "POST /2020/day/1/answer HTTP/1.1");
+    assert_eq!(requests[0].header("Cookie"), Some("session=token"));
+    assert_eq!(requests[0].body, "level=1&answer=514579");
+    assert_eq!(requests[1].body, "level=2&answer=9999");
+
+    // The history is kept on disk for the next runs
+    let client = Client::new(client.config().clone())?;
+    assert!(matches!(
+        client.submit(1, Part::Two, "9999"),
+        Err(Errors::Duplicate {
+            verdict: Verdict::TooHigh,
+            ..
+        })
+    ));
+    assert_eq!(stub.requests().len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_verdicts() -> Result<(), Box<dyn Error>> {
+    let stub = Stub::start(puzzle_server)?;
+
+    let client = empty_client(&stub, "too-low")?;
+    assert_eq!(client.submit(1, Part::One, "1")?, Verdict::TooLow);
+    assert!(matches!(
+        client.submit(1, Part::One, "2"),
+        Err(Errors::Cooldown(seconds)) if seconds > 60
+    ));
+
+    let client = empty_client(&stub, "incorrect")?;
+    assert_eq!(client.submit(1, Part::One, "42")?, Verdict::Incorrect);
+
+    let client = empty_client(&stub, "wait")?;
+    let wait = Verdict::Wait(Duration::from_secs(4 * 60 + 23));
+    assert_eq!(client.submit(1, Part::One, "7")?, wait);
+    assert!(matches!(
+        client.submit(1, Part::One, "7"),
+        Err(Errors::Cooldown(_))
+    ));
+
+    // Unchecked answers are not in the history, they can be submitted again
+    let client = empty_client(&stub, "wrong-level")?;
+    assert_eq!(client.submit(1, Part::Two, "0")?, Verdict::WrongLevel);
+    assert_eq!(client.submit(1, Part::Two, "0")?, Verdict::WrongLevel);
+
+    assert_eq!(stub.requests().len(), 5);
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::collections::HashSet;
+
+const ENTRIES: usize = 1_000_000;
+
+/// A million entries between 1011 and 2000 which can not be part of any sum, then the only pair
+/// and triple summing to 2020 at the very end
+fn expense_report() -> Vec<i64> {
+    // Xorshift, deterministic so the runs compare
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut entries: Vec<i64> = (0..ENTRIES)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            1011 + (state % 990) as i64
+        })
+        .collect();
+    entries.extend([10, 2010, 5, 6, 2009].iter());
+    entries
+}
+
+/// Pair search of the `HashSet` implementation the tables replace
+fn hash_pair(numbers: &[i64]) -> Option<i64> {
+    let mut others = HashSet::new();
+    for &number in numbers {
+        let compliment = 2020 - number;
+        if others.contains(&compliment) {
+            return Some(compliment * number);
+        }
+        others.insert(number);
+    }
+    None
+}
+
+/// Triple search of the `HashSet` implementation the tables replace
+fn hash_triple(numbers: &[i64]) -> Option<i64> {
+    let numbers: HashSet<i64> = numbers.iter().copied().collect();
+    numbers
+        .iter()
+        .flat_map(|a| numbers.iter().map(move |b| (a, b)))
+        .find_map(|(&a, &b)| {
+            let compliment = 2020 - a - b;
+            (a != b && a != compliment && b != compliment && numbers.contains(&compliment))
+                .then(|| a * b * compliment)
+        })
+}
+
+fn million_entries(c: &mut Criterion) {
+    let report = expense_report();
+    let mut group = c.benchmark_group("day01_million");
+    group.sample_size(10);
+
+    group.bench_with_input(BenchmarkId::new("hash", "pair"), &report, |b, report| {
+        b.iter(|| hash_pair(black_box(report)))
+    });
+    group.bench_with_input(BenchmarkId::new("hash", "triple"), &report, |b, report| {
+        b.iter(|| hash_triple(black_box(report)))
+    });
+    group.bench_with_input(BenchmarkId::new("dense", "pair"), &report, |b, report| {
+        b.iter(|| day1::find_k_sum(black_box(report), 2, 2020))
+    });
+    group.bench_with_input(BenchmarkId::new("dense", "triple"), &report, |b, report| {
+        b.iter(|| day1::find_k_sum(black_box(report), 3, 2020))
+    });
+    group.bench_with_input(BenchmarkId::new("dense", "subset"), &report, |b, report| {
+        b.iter(|| {
+            day1::DenseReport::new(black_box(report), 2020).and_then(|dense| dense.subset_sum())
+        })
+    });
+    group.bench_with_input(
+        BenchmarkId::new("sorted", "triple"),
+        &report,
+        |b, report| b.iter(|| day1::all_k_sums(black_box(report), 3, 2020)),
+    );
+
+    group.finish();
+}
+
+criterion_group!(benches, million_entries);
+criterion_main!(benches);
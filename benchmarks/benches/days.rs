@@ -0,0 +1,133 @@
+use criterion::{black_box, BenchmarkId, Criterion};
+use std::{fs, time::SystemTime};
+use utils::Solution;
+
+/// Benchmark the parsing and both parts of a day on its real input and on its example
+fn bench_day<S: Solution>(
+    c: &mut Criterion,
+    ids: &mut Vec<String>,
+    day: u8,
+    real: &S,
+    example: &S,
+) {
+    let group_name = format!("day{:02}", day);
+    let mut group = c.benchmark_group(&group_name);
+
+    let inputs = [
+        (
+            "real",
+            real,
+            aoc::default_input_path(&benchmarks::workspace_root(), day),
+        ),
+        ("example", example, benchmarks::example_path(day)),
+    ];
+
+    for (label, solution, path) in inputs.iter() {
+        let input = match fs::read_to_string(path) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Skipping {} {}: {}", group_name, label, e);
+                continue;
+            }
+        };
+
+        let parsed = solution
+            .parse(&input)
+            .unwrap_or_else(|e| panic!("Parsing {} {} failed: {}", group_name, label, e));
+
+        group.bench_with_input(BenchmarkId::new("parse", label), &input, |b, input| {
+            b.iter(|| solution.parse(black_box(input)).is_ok())
+        });
+        group.bench_with_input(BenchmarkId::new("part1", label), &parsed, |b, parsed| {
+            b.iter(|| solution.part1(black_box(parsed)).is_ok())
+        });
+        group.bench_with_input(BenchmarkId::new("part2", label), &parsed, |b, parsed| {
+            b.iter(|| solution.part2(black_box(parsed)).is_ok())
+        });
+
+        for phase in ["parse", "part1", "part2"].iter() {
+            ids.push(format!("{}/{}/{}", group_name, phase, label));
+        }
+    }
+
+    group.finish();
+}
+
+fn benches(c: &mut Criterion, ids: &mut Vec<String>) {
+    bench_day(c, ids, 1, &day1::Day1, &day1::Day1);
+    bench_day(c, ids, 2, &day2::Day2, &day2::Day2);
+    bench_day(c, ids, 3, &day3::Day3, &day3::Day3);
+    bench_day(c, ids, 4, &day4::Day4, &day4::Day4);
+    bench_day(c, ids, 5, &day5::Day5, &day5::Day5);
+    bench_day(c, ids, 6, &day6::Day6, &day6::Day6);
+    bench_day(c, ids, 7, &day7::Day7, &day7::Day7);
+    bench_day(c, ids, 8, &day8::Day8, &day8::Day8);
+    bench_day(
+        c,
+        ids,
+        9,
+        &day9::Day9::default(),
+        &day9::Day9 { preamble_len: 5 },
+    );
+    bench_day(c, ids, 10, &day10::Day10, &day10::Day10);
+    bench_day(c, ids, 11, &day11::Day11, &day11::Day11);
+    bench_day(c, ids, 12, &day12::Day12, &day12::Day12);
+    bench_day(c, ids, 13, &day13::Day13, &day13::Day13);
+    bench_day(c, ids, 14, &day14::Day14, &day14::Day14);
+    bench_day(
+        c,
+        ids,
+        15,
+        &day15::Day15::default(),
+        &day15::Day15::default(),
+    );
+    bench_day(
+        c,
+        ids,
+        16,
+        &day16::Day16::default(),
+        &day16::Day16 { key: "class" },
+    );
+    bench_day(
+        c,
+        ids,
+        17,
+        &day17::Day17::default(),
+        &day17::Day17::default(),
+    );
+    bench_day(c, ids, 18, &day18::Day18, &day18::Day18);
+    bench_day(c, ids, 19, &day19::Day19, &day19::Day19);
+}
+
+fn main() {
+    let start = SystemTime::now();
+
+    // Some solvers take seconds per iteration, keep the sample count to the minimum
+    let mut criterion = Criterion::default().sample_size(10).configure_from_args();
+    let mut ids = Vec::new();
+    benches(&mut criterion, &mut ids);
+    criterion.final_summary();
+
+    // Benchmarks filtered out on the command line may have stale results, only summarize this run
+    let home = benchmarks::criterion_home();
+    ids.retain(|id| {
+        fs::metadata(home.join(id).join("new").join("estimates.json"))
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified >= start)
+    });
+    if ids.is_empty() {
+        return;
+    }
+
+    match benchmarks::summary(&home, &ids) {
+        Ok(summary) => {
+            let path = home.join("summary.tsv");
+            match fs::write(&path, &summary) {
+                Ok(()) => println!("Summary written to {}", path.display()),
+                Err(e) => eprintln!("Could not write {}: {}", path.display(), e),
+            }
+            print!("{}", summary);
+        }
+        Err(e) => eprintln!("Could not summarize the results: {}", e),
+    }
+}
@@ -0,0 +1,94 @@
+use displaydoc::Display;
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error, Display)]
+pub enum Errors {
+    /// Could not read the results of {0}
+    Io(String, #[source] io::Error),
+    /// Could not parse the results of {0}
+    Json(String, #[source] serde_json::Error),
+    /// No mean estimate in the results of {0}
+    MissingEstimate(String),
+}
+
+/// Root of the workspace, the real inputs are found relative to it
+pub fn workspace_root() -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    manifest_dir.parent().unwrap_or(manifest_dir).to_path_buf()
+}
+
+/// Example input for a day, shipped along with the benchmarks
+pub fn example_path(day: u8) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join(format!("day{}.txt", day))
+}
+
+/// Directory criterion writes its results to, resolved the same way criterion does
+pub fn criterion_home() -> PathBuf {
+    env::var_os("CRITERION_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("CARGO_TARGET_DIR").map(|dir| Path::new(&dir).join("criterion")))
+        .unwrap_or_else(|| workspace_root().join("target").join("criterion"))
+}
+
+/// Mean time in nanoseconds of a benchmark from the latest criterion run
+pub fn mean_estimate(criterion_home: &Path, id: &str) -> Result<f64, Errors> {
+    let path = criterion_home.join(id).join("new").join("estimates.json");
+    let content = fs::read_to_string(&path).map_err(|e| Errors::Io(id.to_string(), e))?;
+    let estimates: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| Errors::Json(id.to_string(), e))?;
+
+    estimates["mean"]["point_estimate"]
+        .as_f64()
+        .ok_or_else(|| Errors::MissingEstimate(id.to_string()))
+}
+
+/// Summary of the given benchmarks, one `<id>\t<mean ns>` line each sorted by id so it diffs cleanly
+pub fn summary(criterion_home: &Path, ids: &[String]) -> Result<String, Errors> {
+    let mut ids = ids.to_vec();
+    ids.sort();
+
+    let mut summary = String::new();
+    for id in ids {
+        let mean = mean_estimate(criterion_home, &id)?;
+        summary.push_str(&format!("{}\t{:.0}\n", id, mean));
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    #[test]
+    fn test_summary() -> Result<(), Box<dyn std::error::Error>> {
+        let home = std::env::temp_dir().join(format!("benchmarks-summary-{}", std::process::id()));
+        for (id, mean) in [("day02/parse/real", 20.4), ("day10/part1/example", 1234.6)].iter() {
+            let dir = home.join(id).join("new");
+            fs::create_dir_all(&dir)?;
+            fs::write(
+                dir.join("estimates.json"),
+                format!(r#"{{"mean":{{"point_estimate":{}}}}}"#, mean),
+            )?;
+        }
+
+        let summary = super::summary(
+            &home,
+            &[
+                "day02/parse/real".to_string(),
+                "day10/part1/example".to_string(),
+            ],
+        )?;
+        assert_eq!(summary, "day02/parse/real\t20\nday10/part1/example\t1235\n");
+
+        assert!(super::summary(&home, &["day03/parse/real".to_string()]).is_err());
+        fs::remove_dir_all(home)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+use std::convert::TryFrom;
+
+use crate::KSum;
+
+/// Largest target the tables are built for, they take a few bytes per possible sum
+pub const MAX_DENSE_TARGET: i64 = 1 << 20;
+
+/// Expense report whose entries are all between 0 and the target, so the entries can index tables
+/// sized by the target instead of being hashed or sorted
+pub struct DenseReport<'r> {
+    values: &'r [i64],
+    target: usize,
+    /// Number of entries of each value
+    counts: Vec<u32>,
+    /// Values of the entries, once each in increasing order
+    present: Vec<usize>,
+}
+
+impl<'r> DenseReport<'r> {
+    /// Count the entries of `values`, `None` if one of them is negative or above the target, or if
+    /// the target is above [`MAX_DENSE_TARGET`]
+    pub fn new(values: &'r [i64], target: i64) -> Option<Self> {
+        if !(0..=MAX_DENSE_TARGET).contains(&target) {
+            return None;
+        }
+
+        let target = target as usize;
+        let mut counts = vec![0_u32; target + 1];
+        for &value in values {
+            let value = usize::try_from(value)
+                .ok()
+                .filter(|&value| value <= target)?;
+            counts[value] = counts[value].saturating_add(1);
+        }
+        let present = (0..=target).filter(|&value| counts[value] > 0).collect();
+
+        Some(Self {
+            values,
+            target,
+            counts,
+            present,
+        })
+    }
+
+    /// Same as [`find_k_sum`](crate::find_k_sum), the last entry is looked up in the table
+    ///
+    /// Takes `O(d^(k-1))` where `d` is the number of distinct values, at most the target plus one.
+    pub fn find_k_sum(&self, k: usize) -> Option<KSum> {
+        if k > self.values.len() {
+            return None;
+        }
+        let mut picked = Vec::with_capacity(k);
+        if self.pick(0, k, self.target, &mut picked) {
+            Some(self.witness(picked))
+        } else {
+            None
+        }
+    }
+
+    /// Any number of entries summing to the target, the sums reachable so far are kept in a bitset
+    ///
+    /// The entries of a value already seen `target / value` times can not reach any new sum and
+    /// are skipped, so whatever the length of the report this takes `O(target² log(target) / 64)`
+    /// on top of reading it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let report = [1500, 900, 300, 700, 120, 1500];
+    /// let subset = day1::DenseReport::new(&report, 2020).unwrap().subset_sum().unwrap();
+    /// assert_eq!(subset.values.iter().sum::<i64>(), 2020);
+    ///
+    /// assert!(day1::DenseReport::new(&[1500, 900], 2020).unwrap().subset_sum().is_none());
+    /// ```
+    pub fn subset_sum(&self) -> Option<KSum> {
+        let words = self.target / 64 + 1;
+        let mut reachable = vec![0_u64; words];
+        reachable[0] = 1;
+        // Entry the sum was first reached with, from a sum reached with earlier entries only
+        let mut via = vec![usize::MAX; self.target + 1];
+        let mut seen = vec![0_u32; self.target + 1];
+
+        for (index, &value) in self.values.iter().enumerate() {
+            if self.target == 0 || via[self.target] != usize::MAX {
+                break;
+            }
+            let value = value as usize;
+            if value == 0 || seen[value] as usize >= self.target / value {
+                continue;
+            }
+            seen[value] += 1;
+
+            // From the top so that the lower words still hold the sums reached without this entry
+            let (shift, offset) = (value / 64, value % 64);
+            for word in (shift..words).rev() {
+                let mut shifted = reachable[word - shift] << offset;
+                if offset > 0 && word > shift {
+                    shifted |= reachable[word - shift - 1] >> (64 - offset);
+                }
+
+                let mut new = shifted & !reachable[word];
+                reachable[word] |= new;
+                while new != 0 {
+                    let sum = word * 64 + new.trailing_zeros() as usize;
+                    if sum <= self.target {
+                        via[sum] = index;
+                    }
+                    new &= new - 1;
+                }
+            }
+        }
+
+        let mut indices = Vec::new();
+        let mut sum = self.target;
+        while sum > 0 {
+            let index = *via.get(sum).filter(|&&index| index != usize::MAX)?;
+            indices.push(index);
+            sum -= self.values[index] as usize;
+        }
+        indices.sort_unstable();
+
+        Some(KSum {
+            values: indices.iter().map(|&index| self.values[index]).collect(),
+            indices,
+        })
+    }
+
+    /// Pick `k` more values from `present[from..]` summing to `remaining`, in increasing order and
+    /// never more of a value than there are entries of it
+    fn pick(&self, from: usize, k: usize, remaining: usize, picked: &mut Vec<usize>) -> bool {
+        match k {
+            0 => remaining == 0,
+            1 => {
+                let last = picked.last().copied().unwrap_or(0);
+                let found = remaining >= last && self.available(remaining, picked);
+                if found {
+                    picked.push(remaining);
+                }
+                found
+            }
+            _ => {
+                for (offset, &value) in self.present[from..].iter().enumerate() {
+                    if value.checked_mul(k).is_none_or(|least| least > remaining) {
+                        break;
+                    }
+                    if !self.available(value, picked) {
+                        continue;
+                    }
+
+                    picked.push(value);
+                    if self.pick(from + offset, k - 1, remaining - value, picked) {
+                        return true;
+                    }
+                    picked.pop();
+                }
+                false
+            }
+        }
+    }
+
+    /// Whether there is an entry of `value` left once the `picked` ones are taken
+    fn available(&self, value: usize, picked: &[usize]) -> bool {
+        let taken = picked.iter().filter(|&&other| other == value).count();
+        self.counts
+            .get(value)
+            .is_some_and(|&count| count as usize > taken)
+    }
+
+    /// First entries of the `picked` values in the report
+    fn witness(&self, mut picked: Vec<usize>) -> KSum {
+        let mut indices = Vec::with_capacity(picked.len());
+        for (index, &value) in self.values.iter().enumerate() {
+            if picked.is_empty() {
+                break;
+            }
+            if let Some(position) = picked.iter().position(|&other| other as i64 == value) {
+                picked.swap_remove(position);
+                indices.push(index);
+            }
+        }
+
+        KSum {
+            values: indices.iter().map(|&index| self.values[index]).collect(),
+            indices,
+        }
+    }
+}
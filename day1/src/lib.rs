@@ -1,34 +1,279 @@
-#![allow(unstable_name_collisions)]
-use std::collections::HashSet;
-use utils::BoolExt as _;
-
-pub fn challenge1(input: &str) -> i64 {
-    let mut others = HashSet::new();
-    input
-        .lines()
-        .map(|line| i64::from_str_radix(line, 10).expect("Bad radix value"))
-        .find_map(|number| {
-            let compliment = 2020 - number;
-            others.insert(number);
-            others.contains(&compliment).then(|| compliment * number)
-        })
-        .expect("Nothing matched")
+use displaydoc::Display;
+use std::{collections::HashSet, io::BufRead, ops::ControlFlow};
+use thiserror::Error;
+use utils::{Diagnostic, ReadError, Solution};
+
+mod dense;
+
+pub use dense::{DenseReport, MAX_DENSE_TARGET};
+
+#[derive(Debug, Error, Display)]
+pub enum Errors {
+    /// Invalid input: {0}
+    Invalid(#[from] Diagnostic),
+    /// {0}
+    Read(#[from] ReadError),
+    /// Parse error {0}
+    ParseError(#[from] std::num::ParseIntError),
+    /// No solution was found
+    NoSolutionFound,
+    /// Answer does not fit in 64 bits
+    Overflow,
+}
+
+/// Year's expense report target
+pub const TARGET: i64 = 2020;
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input<'i> = Vec<i64>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(entries_product(input, 2, TARGET)?)
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(entries_product(input, 3, TARGET)?)
+    }
 }
 
-pub fn challenge2(input: &str) -> i64 {
-    let numbers: HashSet<i64> = input
-        .lines()
-        .map(|line| i64::from_str_radix(line, 10).expect("Bad radix value"))
-        .collect();
+fn parse(input: &str) -> Result<Vec<i64>, Diagnostic> {
+    utils::parse_lines(input, parse_number)
+}
+
+fn parse_number(line: &str) -> Result<i64, Diagnostic> {
+    line.parse()
+        .map_err(|e| Diagnostic::new(line, line, Errors::from(e)))
+}
+
+/// Entries of the report summing to the target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KSum {
+    /// Positions of the entries in the report, in increasing order
+    pub indices: Vec<usize>,
+    /// Values of the entries, in the order of `indices`
+    pub values: Vec<i64>,
+}
+
+impl KSum {
+    /// Product of the values, `None` if it does not fit in 64 bits
+    pub fn product(&self) -> Option<i64> {
+        self.values
+            .iter()
+            .try_fold(1_i64, |product, &value| product.checked_mul(value))
+    }
+}
 
-    numbers
-        .iter()
-        .flat_map(|a| numbers.iter().map(move |b| (a, b)))
-        .find_map(|(a, b)| {
-            let compliment = 2020 - a - b;
-            numbers.contains(&compliment).then(|| compliment * a * b)
+/// First `k` entries of `values` summing to `target`, each entry is used at most once
+///
+/// A value can only be used as many times as it appears in `values`, so a single `1010` is not a
+/// pair summing to `2020` but two of them are. Runs in `O(n^(k-1))` after sorting, with the last two
+/// entries found by moving two pointers towards each other, unless every entry is between 0 and the
+/// target in which case a [`DenseReport`] is searched instead.
+///
+/// # Examples
+///
+/// ```
+/// let report = [1721, 979, 366, 299, 675, 1456];
+/// let pair = day1::find_k_sum(&report, 2, 2020).unwrap();
+/// assert_eq!((pair.indices, pair.values), (vec![0, 3], vec![1721, 299]));
+///
+/// assert_eq!(day1::find_k_sum(&[1010, 7], 2, 2020), None);
+/// assert!(day1::find_k_sum(&[1010, 7, 1010], 2, 2020).is_some());
+/// ```
+pub fn find_k_sum(values: &[i64], k: usize, target: i64) -> Option<KSum> {
+    if k > values.len() {
+        return None;
+    }
+    if let Some(dense) = DenseReport::new(values, target) {
+        return dense.find_k_sum(k);
+    }
+
+    let mut found = None;
+    k_sums(values, k, target, |solution| {
+        found = Some(solution);
+        ControlFlow::Break(())
+    });
+
+    found
+}
+
+/// Every way to pick `k` entries of `values` summing to `target`, each entry used at most once
+///
+/// Solutions picking the same values from different entries are only listed once, with the
+/// entries that come first in `values`. The solutions are ordered by their values from the
+/// smallest.
+///
+/// # Examples
+///
+/// ```
+/// let solutions = day1::all_k_sums(&[3, 1, 2, 2, 2], 2, 4);
+/// let indices: Vec<Vec<usize>> = solutions.into_iter().map(|s| s.indices).collect();
+/// assert_eq!(indices, vec![vec![0, 1], vec![2, 3]]);
+/// ```
+pub fn all_k_sums(values: &[i64], k: usize, target: i64) -> Vec<KSum> {
+    let mut solutions = Vec::new();
+    k_sums(values, k, target, |solution| {
+        solutions.push(solution);
+        ControlFlow::Continue(())
+    });
+
+    solutions
+}
+
+/// Product of the first `k` entries summing to `target`
+fn entries_product(values: &[i64], k: usize, target: i64) -> Result<i64, Errors> {
+    find_k_sum(values, k, target)
+        .ok_or(Errors::NoSolutionFound)?
+        .product()
+        .ok_or(Errors::Overflow)
+}
+
+/// Callback on the entries of each solution, breaks to stop the search
+type Visit<'v> = dyn FnMut(&[(i64, usize)]) -> ControlFlow<()> + 'v;
+
+/// Call `visit` with each solution until it breaks
+fn k_sums<F>(values: &[i64], k: usize, target: i64, mut visit: F)
+where
+    F: FnMut(KSum) -> ControlFlow<()>,
+{
+    if k > values.len() {
+        return;
+    }
+
+    // Stable, the entries with the same value stay in the order of the report
+    let mut sorted: Vec<(i64, usize)> = values.iter().copied().zip(0..).collect();
+    sorted.sort_by_key(|&(value, _)| value);
+
+    let mut picked = Vec::with_capacity(k);
+    let _ = search(&sorted, k, i128::from(target), &mut picked, &mut |picked| {
+        let mut entries: Vec<(usize, i64)> = picked.iter().map(|&(v, i)| (i, v)).collect();
+        entries.sort_unstable();
+        visit(KSum {
+            indices: entries.iter().map(|&(index, _)| index).collect(),
+            values: entries.iter().map(|&(_, value)| value).collect(),
         })
-        .expect("Nothing matched")
+    });
+}
+
+/// Pick `k` of the `sorted` entries summing to `target`, the sums are computed on 128 bits so
+/// they can not overflow
+fn search(
+    sorted: &[(i64, usize)],
+    k: usize,
+    target: i128,
+    picked: &mut Vec<(i64, usize)>,
+    visit: &mut Visit<'_>,
+) -> ControlFlow<()> {
+    // Even the smallest or the largest entries can not reach the target
+    if k > sorted.len() || sum(&sorted[..k]) > target || sum(&sorted[sorted.len() - k..]) < target {
+        return ControlFlow::Continue(());
+    }
+
+    match k {
+        0 => visit(picked),
+        2 => pairs(sorted, target, picked, visit),
+        _ => {
+            for first in 0..=sorted.len() - k {
+                if first > 0 && sorted[first - 1].0 == sorted[first].0 {
+                    continue;
+                }
+                picked.push(sorted[first]);
+                let rest = target - i128::from(sorted[first].0);
+                let flow = search(&sorted[first + 1..], k - 1, rest, picked, visit);
+                picked.pop();
+                flow?;
+            }
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+fn sum(entries: &[(i64, usize)]) -> i128 {
+    entries.iter().map(|&(value, _)| i128::from(value)).sum()
+}
+
+/// Pairs of the `sorted` entries summing to `target`, from both ends towards the middle
+fn pairs(
+    sorted: &[(i64, usize)],
+    target: i128,
+    picked: &mut Vec<(i64, usize)>,
+    visit: &mut Visit<'_>,
+) -> ControlFlow<()> {
+    let (mut low, mut high) = (0, sorted.len() - 1);
+    while low < high {
+        let sum = i128::from(sorted[low].0) + i128::from(sorted[high].0);
+        if sum < target {
+            low += 1;
+        } else if sum > target {
+            high -= 1;
+        } else {
+            // First entries of both values, the one of `high` can be right after `low`
+            let value = sorted[high].0;
+            let mut first = high;
+            while first > low + 1 && sorted[first - 1].0 == value {
+                first -= 1;
+            }
+
+            picked.extend([sorted[low], sorted[first]]);
+            let flow = visit(picked);
+            picked.truncate(picked.len() - 2);
+            flow?;
+
+            let low_value = sorted[low].0;
+            while low < high && sorted[low].0 == low_value {
+                low += 1;
+            }
+            high = first - 1;
+        }
+    }
+
+    ControlFlow::Continue(())
+}
+
+pub fn challenge1(input: &str) -> Result<i64, Errors> {
+    entries_product(&parse(input)?, 2, TARGET)
+}
+
+pub fn challenge2(input: &str) -> Result<i64, Errors> {
+    entries_product(&parse(input)?, 3, TARGET)
+}
+
+/// [`challenge1`] reading one number at a time, only the distinct values are kept
+///
+/// Each number is checked against the values read before it, so a value only pairs with itself
+/// once it has been read twice. Of the pairs found, the one with the smallest entry is kept: the
+/// same pair [`find_k_sum`] picks.
+pub fn challenge1_from_reader(reader: impl BufRead) -> Result<i64, Errors> {
+    let mut seen = HashSet::new();
+    let mut best: Option<(i64, i64)> = None;
+
+    for number in utils::read_lines(reader, parse_number) {
+        let number = number?;
+        if let Some(complement) = TARGET.checked_sub(number).filter(|c| seen.contains(c)) {
+            let pair = (number.min(complement), number.max(complement));
+            if best.is_none_or(|(smallest, _)| pair.0 < smallest) {
+                best = Some(pair);
+            }
+        }
+        seen.insert(number);
+    }
+
+    let (smallest, largest) = best.ok_or(Errors::NoSolutionFound)?;
+    smallest.checked_mul(largest).ok_or(Errors::Overflow)
+}
+
+/// [`challenge2`] reading one number at a time, every number is kept but not the text
+pub fn challenge2_from_reader(reader: impl BufRead) -> Result<i64, Errors> {
+    let numbers: Vec<i64> = utils::read_lines(reader, parse_number).collect::<Result<_, _>>()?;
+    entries_product(&numbers, 3, TARGET)
 }
 
 #[cfg(test)]
@@ -41,12 +286,196 @@ mod tests {
 1456"#;
 
     #[test]
-    fn first() {
-        assert_eq!(super::challenge1(INPUT), 514579);
+    fn first() -> Result<(), super::Errors> {
+        assert_eq!(super::challenge1(INPUT)?, 514579);
+        assert_eq!(super::challenge1_from_reader(INPUT.as_bytes())?, 514579);
+        Ok(())
     }
 
     #[test]
-    fn second() {
-        assert_eq!(super::challenge2(INPUT), 241861950);
+    fn second() -> Result<(), super::Errors> {
+        assert_eq!(super::challenge2(INPUT)?, 241861950);
+        assert_eq!(super::challenge2_from_reader(INPUT.as_bytes())?, 241861950);
+        Ok(())
+    }
+
+    #[test]
+    fn bad_number() {
+        let error = super::challenge1("1721\n97x\n366").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid input: Line 2, column 1: Parse error invalid digit found in string"
+        );
+        let error = super::challenge1_from_reader("1721\r\n97x\r\n366".as_bytes()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid input: Line 2, column 1: Parse error invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn test_multiplicities() {
+        assert!(matches!(super::challenge1("1010\n1721\n299"), Ok(514579)));
+        assert!(matches!(
+            super::challenge1("1010\n7"),
+            Err(super::Errors::NoSolutionFound)
+        ));
+        assert!(matches!(
+            super::challenge1_from_reader("1010\n7".as_bytes()),
+            Err(super::Errors::NoSolutionFound)
+        ));
+        assert!(matches!(super::challenge1("7\n1010\n1010"), Ok(1020100)));
+        assert!(matches!(
+            super::challenge1_from_reader("1010\n-5\n7\n2025".as_bytes()),
+            Ok(-10125)
+        ));
+        assert!(matches!(
+            super::challenge1_from_reader("7\n1010\n1010".as_bytes()),
+            Ok(1020100)
+        ));
+        assert!(matches!(
+            super::challenge2("505\n1010\n7"),
+            Err(super::Errors::NoSolutionFound)
+        ));
+        assert!(matches!(super::challenge2("505\n1010\n505"), Ok(257575250)));
+    }
+
+    #[test]
+    fn test_several_solutions() -> Result<(), super::Errors> {
+        const SEVERAL: &str = "1000\n1020\n1\n2019\n10\n2010\n9\n1010";
+        assert_eq!(super::challenge1(SEVERAL)?, 2019);
+        assert_eq!(
+            super::challenge1_from_reader(SEVERAL.as_bytes())?,
+            super::challenge1(SEVERAL)?
+        );
+        assert_eq!(
+            super::challenge2_from_reader(SEVERAL.as_bytes())?,
+            super::challenge2(SEVERAL)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_k_sums() {
+        let report = [1721, 979, 366, 299, 675, 1456];
+        let triple = super::find_k_sum(&report, 3, 2020).unwrap();
+        assert_eq!(triple.indices, vec![1, 2, 4]);
+        assert_eq!(triple.values, vec![979, 366, 675]);
+
+        assert_eq!(super::find_k_sum(&report, 0, 0).unwrap().indices, vec![]);
+        assert_eq!(super::find_k_sum(&report, 1, 366).unwrap().indices, vec![2]);
+        assert_eq!(super::find_k_sum(&report, 7, 0), None);
+        assert_eq!(super::find_k_sum(&[2, 3], usize::MAX, 10), None);
+        let dense = super::DenseReport::new(&report, 2020).unwrap();
+        assert_eq!(dense.find_k_sum(usize::MAX), None);
+
+        let extremes = [i64::MAX, i64::MAX, i64::MIN, -2];
+        let triple = super::find_k_sum(&extremes, 3, i64::MAX - 1).unwrap();
+        assert_eq!(triple.indices, vec![0, 1, 2]);
+        assert_eq!(triple.product(), None);
+    }
+
+    /// Distinct sorted values of every `k` entries summing to `target`
+    fn brute_force(values: &[i64], k: usize, target: i64) -> Vec<Vec<i64>> {
+        fn pick(values: &[i64], k: usize, target: i64, picked: &mut Vec<i64>) -> Vec<Vec<i64>> {
+            match values.split_first() {
+                _ if k == 0 => {
+                    let mut sorted = picked.clone();
+                    sorted.sort_unstable();
+                    (target == 0).then_some(sorted).into_iter().collect()
+                }
+                None => vec![],
+                Some((&first, rest)) => {
+                    picked.push(first);
+                    let mut solutions = pick(rest, k - 1, target - first, picked);
+                    picked.pop();
+                    solutions.extend(pick(rest, k, target, picked));
+                    solutions
+                }
+            }
+        }
+
+        let mut solutions = pick(values, k, target, &mut vec![]);
+        solutions.sort_unstable();
+        solutions.dedup();
+        solutions
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_all_k_sums(
+            values in proptest::collection::vec(-5_i64..10, 0..12),
+            k in 0_usize..5,
+            target in -10_i64..25,
+        ) {
+            let mut found: Vec<Vec<i64>> = super::all_k_sums(&values, k, target)
+                .into_iter()
+                .map(|solution| {
+                    for (&index, &value) in solution.indices.iter().zip(&solution.values) {
+                        assert_eq!(values[index], value);
+                    }
+                    let mut sorted = solution.values;
+                    sorted.sort_unstable();
+                    sorted
+                })
+                .collect();
+            found.sort_unstable();
+
+            proptest::prop_assert_eq!(found, brute_force(&values, k, target));
+        }
+
+        #[test]
+        fn test_dense_k_sum(
+            values in proptest::collection::vec(0_i64..30, 0..15),
+            k in 0_usize..5,
+            target in 0_i64..40,
+        ) {
+            let values: Vec<i64> = values.into_iter().filter(|&value| value <= target).collect();
+            let dense = super::DenseReport::new(&values, target).unwrap();
+            let sorted = super::all_k_sums(&values, k, target).into_iter().next();
+            proptest::prop_assert_eq!(dense.find_k_sum(k), sorted);
+        }
+
+        #[test]
+        fn test_subset_sum(
+            values in proptest::collection::vec(0_i64..30, 0..12),
+            target in 0_i64..60,
+        ) {
+            let values: Vec<i64> = values.into_iter().filter(|&value| value <= target).collect();
+            let exists = (0..1_u32 << values.len()).any(|subset| {
+                let picked = values.iter().enumerate().filter(|&(i, _)| subset & 1 << i != 0);
+                picked.map(|(_, value)| value).sum::<i64>() == target
+            });
+
+            let subset = super::DenseReport::new(&values, target).unwrap().subset_sum();
+            proptest::prop_assert_eq!(subset.is_some(), exists);
+            if let Some(subset) = subset {
+                proptest::prop_assert!(subset.indices.windows(2).all(|pair| pair[0] < pair[1]));
+                for (&index, &value) in subset.indices.iter().zip(&subset.values) {
+                    proptest::prop_assert_eq!(values[index], value);
+                }
+                proptest::prop_assert_eq!(subset.values.iter().sum::<i64>(), target);
+            }
+        }
+
+        #[test]
+        fn test_reader_pair(values in proptest::collection::vec(980_i64..1041, 0..20)) {
+            let input: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+            let input = input.join("\n");
+            proptest::prop_assert_eq!(
+                super::challenge1_from_reader(input.as_bytes()).ok(),
+                super::challenge1(&input).ok()
+            );
+        }
+
+        #[test]
+        fn test_planted(planted in generators::day1::expense_report()) {
+            proptest::prop_assert_eq!(super::challenge1(&planted.input)?, planted.part1);
+            proptest::prop_assert_eq!(
+                super::challenge1_from_reader(planted.input.as_bytes())?,
+                planted.part1
+            );
+            proptest::prop_assert_eq!(super::challenge2(&planted.input)?, planted.part2);
+        }
     }
 }
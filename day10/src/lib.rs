@@ -2,21 +2,59 @@ use displaydoc::Display;
 use itertools::Itertools as _;
 use std::collections::{BTreeMap, BTreeSet};
 use thiserror::Error;
+use utils::{Diagnostic, Solution};
+
+#[cfg(any(test, feature = "reference"))]
+pub mod reference;
 
 #[derive(Debug, Error, Display)]
 pub enum Errors {
+    /// Invalid input: {0}
+    Invalid(#[from] Diagnostic),
     /// Parse error {0}
     ParseError(#[from] std::num::ParseIntError),
     /// No solution found
     NoSolution,
+    /// Answer does not fit in 64 bits
+    Overflow,
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input<'i> = BTreeSet<u64>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(jolt_differences(input.clone())?)
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(arrangements(input)?)
+    }
+}
+
+fn parse(input: &str) -> Result<BTreeSet<u64>, Diagnostic> {
+    utils::parse_lines(input, |line| {
+        line.parse()
+            .map_err(|e| Diagnostic::new(line, line, Errors::from(e)))
+    })
 }
 
 pub fn challenge1(input: &str) -> Result<u64, Errors> {
-    let mut jolts: BTreeSet<u64> = input.lines().map(str::parse).try_collect()?;
-    let max = *jolts.iter().rev().next().ok_or(Errors::NoSolution)?;
+    jolt_differences(parse(input)?)
+}
+
+fn jolt_differences(mut jolts: BTreeSet<u64>) -> Result<u64, Errors> {
+    let max = *jolts.iter().next_back().ok_or(Errors::NoSolution)?;
 
     jolts.insert(0);
-    jolts.insert(max + 3);
+    jolts.insert(max.checked_add(3).ok_or(Errors::Overflow)?);
 
     let (ones, threes): (u64, u64) =
         jolts
@@ -32,27 +70,33 @@ pub fn challenge1(input: &str) -> Result<u64, Errors> {
 }
 
 pub fn challenge2(input: &str) -> Result<u64, Errors> {
-    let jolts: BTreeSet<u64> = input.lines().map(str::parse).try_collect()?;
+    arrangements(&parse(input)?)
+}
 
-    let last = 3 + *jolts.iter().rev().next().ok_or(Errors::NoSolution)?;
+fn arrangements(jolts: &BTreeSet<u64>) -> Result<u64, Errors> {
+    let last = jolts
+        .iter()
+        .next_back()
+        .ok_or(Errors::NoSolution)?
+        .checked_add(3)
+        .ok_or(Errors::Overflow)?;
     // Map of <next jolt to test> : <number of branches leading to it>
     let mut next: BTreeMap<u64, u64> = (1..=3)
         .filter(|o| jolts.contains(o))
         .map(|i| (i, 1))
         .collect();
 
-    let mut complete_branches = 0;
-    while !next.is_empty() {
-        // This condition is checked for the while loop so unwrap
-        let j = *next.keys().next().unwrap();
-        let count = next.remove(&j).unwrap();
-
+    let mut complete_branches: u64 = 0;
+    while let Some((j, count)) = next.pop_first() {
         for value in j + 1..=j + 3 {
-            if value == last {
-                complete_branches += count
+            let branches = if value == last {
+                &mut complete_branches
             } else if jolts.contains(&value) {
-                *next.entry(value).or_insert(0) += count
-            }
+                next.entry(value).or_insert(0)
+            } else {
+                continue;
+            };
+            *branches = branches.checked_add(count).ok_or(Errors::Overflow)?;
         }
     }
 
@@ -118,4 +162,22 @@ mod tests {
         assert_eq!(super::challenge2(INPUT2)?, 19208);
         Ok(())
     }
+
+    proptest::proptest! {
+        #[test]
+        fn test_planted(planted in generators::day10::adapters()) {
+            proptest::prop_assert_eq!(super::challenge1(&planted.input)?, planted.part1);
+            proptest::prop_assert_eq!(super::challenge2(&planted.input)?, planted.part2);
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_reference(input in generators::day10::adapter_bags()) {
+            proptest::prop_assert_eq!(
+                super::challenge2(&input).ok(),
+                super::reference::challenge2(&input).ok()
+            );
+        }
+    }
 }
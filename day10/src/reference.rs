@@ -0,0 +1,35 @@
+//! Brute force solvers, slow but simple enough to check the fast ones against
+
+use std::collections::BTreeSet;
+
+use crate::{parse, Errors};
+
+/// [`challenge2`](crate::challenge2) following every chain of adapters to the device one at a
+/// time, only practical for small bags as there are as many chains as arrangements
+pub fn challenge2(input: &str) -> Result<u64, Errors> {
+    let jolts = parse(input)?;
+    let device = jolts
+        .iter()
+        .next_back()
+        .ok_or(Errors::NoSolution)?
+        .checked_add(3)
+        .ok_or(Errors::Overflow)?;
+
+    chains(&jolts, 0, device)
+}
+
+/// Number of chains from `jolt` to `device` where each adapter is 1 to 3 jolts above the previous
+fn chains(jolts: &BTreeSet<u64>, jolt: u64, device: u64) -> Result<u64, Errors> {
+    if jolt == device {
+        return Ok(1);
+    }
+
+    (1..=3)
+        .filter_map(|step| jolt.checked_add(step))
+        .filter(|next| *next == device || jolts.contains(next))
+        .try_fold(0_u64, |count, next| {
+            count
+                .checked_add(chains(jolts, next, device)?)
+                .ok_or(Errors::Overflow)
+        })
+}
@@ -1,184 +1,169 @@
-#![allow(unstable_name_collisions)]
 use displaydoc::Display;
-use itertools::Itertools as _;
-use std::convert::{TryFrom, TryInto};
+use std::convert::TryFrom;
 use thiserror::Error;
-use utils::BoolExt as _;
+use utils::{grid::SURROUNDING, Diagnostic, Grid, Solution};
 
 #[derive(Debug, Error, Display)]
 pub enum Errors {
     /// No solution was found
     NoSolutionFound,
-    /// Invalid character {0} in input.
-    InvalidCharacter(char),
-    /// An input row is of different length then the others
-    JaggedInput,
+    /// Invalid seat layout: {0}
+    InvalidLayout(#[from] Diagnostic),
 }
 
+/// Invalid character {0:?} in input.
+#[derive(Debug, Error, Display)]
+pub struct InvalidCharacter(char);
+
+/// A position in the seat layout
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum Spot {
+pub enum Spot {
     EmptySeat,
     FilledSeat,
     Isle,
 }
 
 impl TryFrom<char> for Spot {
-    type Error = Errors;
+    type Error = InvalidCharacter;
 
     fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
             '.' => Ok(Spot::Isle),
             '#' => Ok(Spot::FilledSeat),
             'L' => Ok(Spot::EmptySeat),
-            _ => Err(Errors::InvalidCharacter(value)),
+            _ => Err(InvalidCharacter(value)),
         }
     }
 }
 
-/// Add a usize to an isize safely, returns None if the result would be invalid
-fn checked_add(a: usize, b: isize) -> Option<usize> {
-    if b.is_negative() {
-        a.checked_sub(b.checked_neg()?.try_into().ok()?)
-    } else {
-        a.checked_add(b.try_into().ok()?)
-    }
-}
-
 /// Counts the number of neighboring seats that are occupied
-fn neighboring_occupied(plane: &Vec<Vec<Spot>>, pos: (usize, usize)) -> usize {
-    [
-        (-1, 1),
-        (-1, 0),
-        (-1, -1),
-        (0, -1),
-        (1, -1),
-        (1, 0),
-        (1, 1),
-        (0, 1),
-    ]
-    .iter()
-    .filter_map(|&direction| {
-        let row = plane.get(checked_add(pos.0, direction.0)?)?;
-        let spot = row.get(checked_add(pos.1, direction.1)?)?;
-
-        (*spot == Spot::FilledSeat).then(|| ())
-    })
-    .count()
+fn neighboring_occupied(plane: &Grid<Spot>, pos: (usize, usize)) -> usize {
+    plane
+        .neighbors8(pos)
+        .filter(|&(_, &spot)| spot == Spot::FilledSeat)
+        .count()
 }
 
 /// Counts the number of visible seats that are occupied
-fn visible_occupied(plane: &Vec<Vec<Spot>>, pos: (usize, usize)) -> usize {
-    [
-        (-1, 1),
-        (-1, 0),
-        (-1, -1),
-        (0, -1),
-        (1, -1),
-        (1, 0),
-        (1, 1),
-        (0, 1),
-    ]
-    .iter()
-    .filter(|&&direction| see_occupied(plane, pos, direction))
-    .count()
+fn visible_occupied(plane: &Grid<Spot>, pos: (usize, usize)) -> usize {
+    SURROUNDING
+        .iter()
+        .filter(|&&direction| see_occupied(plane, pos, direction))
+        .count()
 }
 
 /// Check if the next visible seat is occupied
-fn see_occupied(plane: &Vec<Vec<Spot>>, pos: (usize, usize), direction: (isize, isize)) -> bool {
-    checked_add(pos.0, direction.0)
-        .zip(checked_add(pos.1, direction.1))
-        .map(
-            |next| match plane.get(next.0).and_then(|row| row.get(next.1)) {
-                Some(Spot::FilledSeat) => true,
-                Some(Spot::Isle) => see_occupied(plane, next, direction),
-                Some(Spot::EmptySeat) => false,
-                None => false,
-            },
-        )
-        .unwrap_or(false)
+fn see_occupied(plane: &Grid<Spot>, pos: (usize, usize), direction: (isize, isize)) -> bool {
+    plane
+        .ray(pos, direction)
+        .map(|(_, &spot)| spot)
+        .find(|&spot| spot != Spot::Isle)
+        == Some(Spot::FilledSeat)
 }
 
-/// Traverse the 2D map yielding cells and their positions
-fn traverse(plane: &Vec<Vec<Spot>>) -> impl Iterator<Item = (&Spot, (usize, usize))> {
-    plane.iter().enumerate().flat_map(|(row_i, row)| {
-        row.iter()
-            .enumerate()
-            .map(move |(col_i, spot)| (spot, (row_i, col_i)))
-    })
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input<'i> = Grid<Spot>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse_layout(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(stable_occupied_neighbors(input.clone()))
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(stable_occupied_visible(input.clone()))
+    }
 }
 
-fn parse_layout(input: &str) -> Result<Vec<Vec<Spot>>, Errors> {
-    input
-        .lines()
-        .map(|line| line.chars().map(Spot::try_from).try_collect())
-        .try_collect()
+fn parse_layout(input: &str) -> Result<Grid<Spot>, Errors> {
+    let layout: Grid<Spot> = input.parse()?;
+    if layout.width() == 0 {
+        return Err(Errors::NoSolutionFound);
+    }
+
+    Ok(layout)
 }
 
 pub fn challenge1(input: &str) -> Result<usize, Errors> {
-    let mut current = parse_layout(input)?;
-    let width = current.first().ok_or(Errors::NoSolutionFound)?.len();
-    if current.iter().any(|row| row.len() != width) {
-        return Err(Errors::JaggedInput);
-    }
+    Ok(stable_occupied_neighbors(parse_layout(input)?))
+}
 
+/// Simulate seating using the direct neighbors until it stabilizes, returns the occupied seat count
+#[tracing::instrument(level = "debug", skip_all)]
+fn stable_occupied_neighbors(mut current: Grid<Spot>) -> usize {
     let mut previous = current.clone();
+    let mut generation = 0;
 
     loop {
+        generation += 1;
         std::mem::swap(&mut current, &mut previous);
 
-        for (&spot, position) in traverse(&previous) {
-            current[position.0][position.1] =
-                match (spot, neighboring_occupied(&previous, position)) {
-                    (Spot::FilledSeat, 4..=8) => Spot::EmptySeat,
-                    (Spot::EmptySeat, 0) => Spot::FilledSeat,
-                    (spot, _) => spot,
-                };
+        for (position, &spot) in previous.iter() {
+            current[position] = match (spot, neighboring_occupied(&previous, position)) {
+                (Spot::FilledSeat, 4..=8) => Spot::EmptySeat,
+                (Spot::EmptySeat, 0) => Spot::FilledSeat,
+                (spot, _) => spot,
+            };
         }
 
+        tracing::debug!(generation, changed = changed_count(&previous, &current));
         if current == previous {
-            let occupied_count = current
-                .iter()
-                .flat_map(|r| r.iter())
-                .filter(|&&s| s == Spot::FilledSeat)
-                .count();
-
-            return Ok(occupied_count);
+            return occupied_count(&current);
         }
     }
 }
 
 pub fn challenge2(input: &str) -> Result<usize, Errors> {
-    let mut current = parse_layout(input)?;
-    let width = current.first().ok_or(Errors::NoSolutionFound)?.len();
-    if current.iter().any(|row| row.len() != width) {
-        return Err(Errors::JaggedInput);
-    }
+    Ok(stable_occupied_visible(parse_layout(input)?))
+}
 
+/// Simulate seating using the visible seats until it stabilizes, returns the occupied seat count
+#[tracing::instrument(level = "debug", skip_all)]
+fn stable_occupied_visible(mut current: Grid<Spot>) -> usize {
     let mut previous = current.clone();
+    let mut generation = 0;
 
     loop {
+        generation += 1;
         std::mem::swap(&mut current, &mut previous);
 
-        for (&spot, position) in traverse(&previous) {
-            current[position.0][position.1] = match (spot, visible_occupied(&previous, position)) {
+        for (position, &spot) in previous.iter() {
+            current[position] = match (spot, visible_occupied(&previous, position)) {
                 (Spot::FilledSeat, 5..=8) => Spot::EmptySeat,
                 (Spot::EmptySeat, 0) => Spot::FilledSeat,
                 (spot, _) => spot,
             };
         }
 
+        tracing::debug!(generation, changed = changed_count(&previous, &current));
         if current == previous {
-            let occupied_count = current
-                .iter()
-                .flat_map(|r| r.iter())
-                .filter(|&&s| s == Spot::FilledSeat)
-                .count();
-
-            return Ok(occupied_count);
+            return occupied_count(&current);
         }
     }
 }
 
+/// Seats that changed in a generation, only counted when the progress is traced
+fn changed_count(previous: &Grid<Spot>, current: &Grid<Spot>) -> usize {
+    previous
+        .iter()
+        .zip(current.iter())
+        .filter(|((_, before), (_, after))| before != after)
+        .count()
+}
+
+fn occupied_count(plane: &Grid<Spot>) -> usize {
+    plane
+        .iter()
+        .filter(|&(_, &spot)| spot == Spot::FilledSeat)
+        .count()
+}
+
 #[cfg(test)]
 mod tests {
     const INPUT: &str = r#"L.LL.LL.LL
@@ -203,4 +188,21 @@ L.LLLLL.LL"#;
         assert_eq!(super::challenge2(INPUT)?, 26);
         Ok(())
     }
+
+    #[test]
+    fn test_invalid_layout() {
+        match super::challenge1("L.L\nLL") {
+            Err(super::Errors::InvalidLayout(diagnostic)) => assert_eq!(diagnostic.line(), 2),
+            _ => panic!("Jagged layout was accepted"),
+        }
+        match super::challenge1("L.L\nLxL") {
+            Err(super::Errors::InvalidLayout(diagnostic)) => {
+                assert_eq!((diagnostic.line(), diagnostic.columns()), (2, 2..3));
+                assert!(diagnostic
+                    .cause()
+                    .is::<utils::GridError<super::InvalidCharacter>>());
+            }
+            _ => panic!("Invalid character was accepted"),
+        }
+    }
 }
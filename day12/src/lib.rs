@@ -1,19 +1,26 @@
-#![allow(unstable_name_collisions)]
 use displaydoc::Display;
 use std::{
     f64::consts::PI,
+    io::BufRead,
     ops::{AddAssign, Mul, MulAssign},
 };
 use thiserror::Error;
+use utils::{Diagnostic, ReadError, Solution};
 
 #[derive(Debug, Error, Display)]
 pub enum Errors {
+    /// Invalid input: {0}
+    Invalid(#[from] Diagnostic),
+    /// {0}
+    Read(#[from] ReadError),
     /// Parse float error {0}
     ParseFloatError(#[from] std::num::ParseFloatError),
     /// No solution was found
     NoSolutionFound,
-    /// Could not interpret the line "{0}"
-    BadInputLine(String),
+    /// Expected an action letter followed by a number
+    BadInputLine,
+    /// Unknown action {0:?}, expected one of N, S, E, W, L, R or F
+    UnknownAction(char),
 }
 
 /// Fixed point position, will only be accurate if the angle is exclusively on 90deg increments
@@ -87,7 +94,7 @@ impl AddAssign<Rotation> for Position {
 
 impl MulAssign<Rotation> for Displacement {
     fn mul_assign(&mut self, rhs: Rotation) {
-        let Displacement { east, north } = self.clone();
+        let Displacement { east, north } = *self;
         let sin = rhs.0.sin();
         let cos = rhs.0.cos();
 
@@ -116,52 +123,142 @@ impl AddAssign<Forward> for Position {
     }
 }
 
+/// Navigation action, the letter at the start of an instruction
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Action {
+    North,
+    South,
+    East,
+    West,
+    Left,
+    Right,
+    Forward,
+}
+
+/// Navigation instruction, an action and its magnitude
+#[derive(Debug, Copy, Clone)]
+pub struct Instruction {
+    action: Action,
+    magnitude: f64,
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input<'i> = Vec<Instruction>;
+    type Answer1 = f64;
+    type Answer2 = f64;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(navigate_ship(input.iter().copied().map(Ok))?)
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(navigate_waypoint(input.iter().copied().map(Ok))?)
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<Instruction>, Diagnostic> {
+    utils::parse_lines(input, parse_instruction)
+}
+
+fn parse_instruction(line: &str) -> Result<Instruction, Diagnostic> {
+    let mut chars = line.chars();
+    let action = chars
+        .next()
+        .ok_or_else(|| Diagnostic::new(line, line, Errors::BadInputLine))?;
+    let magnitude = chars.as_str();
+
+    let magnitude = magnitude
+        .parse()
+        .map_err(|e| Diagnostic::new(line, magnitude, Errors::from(e)))?;
+    let action = match action {
+        'N' => Action::North,
+        'S' => Action::South,
+        'E' => Action::East,
+        'W' => Action::West,
+        'L' => Action::Left,
+        'R' => Action::Right,
+        'F' => Action::Forward,
+        c => {
+            let token = &line[..c.len_utf8()];
+            return Err(Diagnostic::new(line, token, Errors::UnknownAction(c)));
+        }
+    };
+
+    Ok(Instruction { action, magnitude })
+}
+
 pub fn challenge1(input: &str) -> Result<f64, Errors> {
+    navigate_ship(parse(input)?.into_iter().map(Ok))
+}
+
+fn navigate_ship<I>(instructions: I) -> Result<f64, Errors>
+where
+    I: IntoIterator<Item = Result<Instruction, Errors>>,
+{
     let mut position = Position::default();
 
-    for line in input.lines() {
-        let magnitude = line[1..].parse()?;
-
-        match &line[0..1] {
-            "R" => position += Rotation::from_deg(magnitude),
-            "L" => position += Rotation::from_deg(-magnitude),
-            "N" => position += Displacement::north(magnitude),
-            "S" => position += Displacement::south(magnitude),
-            "E" => position += Displacement::east(magnitude),
-            "W" => position += Displacement::west(magnitude),
-            "F" => position += Forward(magnitude),
-            _ => return Err(Errors::BadInputLine(line.to_string())),
+    for instruction in instructions {
+        let Instruction { action, magnitude } = instruction?;
+        match action {
+            Action::Right => position += Rotation::from_deg(magnitude),
+            Action::Left => position += Rotation::from_deg(-magnitude),
+            Action::North => position += Displacement::north(magnitude),
+            Action::South => position += Displacement::south(magnitude),
+            Action::East => position += Displacement::east(magnitude),
+            Action::West => position += Displacement::west(magnitude),
+            Action::Forward => position += Forward(magnitude),
         }
     }
 
     Ok((position.east.abs() + position.north.abs()).round())
 }
 
+/// [`challenge1`] reading one instruction at a time
+pub fn challenge1_from_reader(reader: impl BufRead) -> Result<f64, Errors> {
+    navigate_ship(utils::read_lines(reader, parse_instruction).map(|i| Ok(i?)))
+}
+
 pub fn challenge2(input: &str) -> Result<f64, Errors> {
+    navigate_waypoint(parse(input)?.into_iter().map(Ok))
+}
+
+fn navigate_waypoint<I>(instructions: I) -> Result<f64, Errors>
+where
+    I: IntoIterator<Item = Result<Instruction, Errors>>,
+{
     let mut position = Position::default();
     let mut waypoint = Displacement {
         east: 10.0,
         north: 1.0,
     };
 
-    for line in input.lines() {
-        let magnitude = line[1..].parse()?;
-
-        match &line[0..1] {
-            "R" => waypoint *= Rotation::from_deg(magnitude),
-            "L" => waypoint *= Rotation::from_deg(-magnitude),
-            "N" => waypoint += Displacement::north(magnitude),
-            "S" => waypoint += Displacement::south(magnitude),
-            "E" => waypoint += Displacement::east(magnitude),
-            "W" => waypoint += Displacement::west(magnitude),
-            "F" => position += waypoint * magnitude,
-            _ => return Err(Errors::BadInputLine(line.to_string())),
+    for instruction in instructions {
+        let Instruction { action, magnitude } = instruction?;
+        match action {
+            Action::Right => waypoint *= Rotation::from_deg(magnitude),
+            Action::Left => waypoint *= Rotation::from_deg(-magnitude),
+            Action::North => waypoint += Displacement::north(magnitude),
+            Action::South => waypoint += Displacement::south(magnitude),
+            Action::East => waypoint += Displacement::east(magnitude),
+            Action::West => waypoint += Displacement::west(magnitude),
+            Action::Forward => position += waypoint * magnitude,
         }
     }
 
     Ok((position.east.abs() + position.north.abs()).round())
 }
 
+/// [`challenge2`] reading one instruction at a time
+pub fn challenge2_from_reader(reader: impl BufRead) -> Result<f64, Errors> {
+    navigate_waypoint(utils::read_lines(reader, parse_instruction).map(|i| Ok(i?)))
+}
+
 #[cfg(test)]
 mod tests {
     const INPUT: &str = r#"F10
@@ -173,12 +270,14 @@ F11"#;
     #[test]
     fn test_challenge1() -> Result<(), super::Errors> {
         assert_eq!(super::challenge1(INPUT)?, 25.0);
+        assert_eq!(super::challenge1_from_reader(INPUT.as_bytes())?, 25.0);
         Ok(())
     }
 
     #[test]
     fn test_challenge2() -> Result<(), super::Errors> {
         assert_eq!(super::challenge2(INPUT)?, 286.0);
+        assert_eq!(super::challenge2_from_reader(INPUT.as_bytes())?, 286.0);
         Ok(())
     }
 }
@@ -1,82 +1,135 @@
-#![allow(unstable_name_collisions)]
 use displaydoc::Display;
-use itertools::Itertools as _;
+use std::convert::TryFrom;
 use thiserror::Error;
-use utils::BoolExt as _;
+use utils::{
+    modular::{self, ModularError},
+    Diagnostic, Solution,
+};
+
+#[cfg(any(test, feature = "reference"))]
+pub mod reference;
 
 #[derive(Debug, Error, Display)]
 pub enum Errors {
+    /// Invalid input: {0}
+    Invalid(#[from] Diagnostic),
     /// Parse error {0}
     ParseError(#[from] std::num::ParseIntError),
     /// No solution was found
     NoSolutionFound,
-    /// Invalid input
+    /// Input ended early, expected the departure time then the bus ids
     InvalidInput,
+    /// Bus ids must be greater than zero
+    ZeroBusId,
+    /// Answer does not fit in 64 bits
+    Overflow,
+    /// Buses never align: {0}
+    NoAlignment(#[from] ModularError),
 }
 
-pub fn challenge1(input: &str) -> Result<u64, Errors> {
-    let lines = input.lines().collect::<Vec<&str>>();
-    let departure_time: u64 = lines.get(0).ok_or(Errors::InvalidInput)?.parse()?;
-    let bus_ids: Vec<u64> = lines
-        .get(1)
-        .ok_or(Errors::InvalidInput)?
-        .split(',')
-        .filter(|&s| s != "x")
-        .map(str::parse)
-        .try_collect()?;
-
-    let (next_id, next_departure) = bus_ids
-        .into_iter()
-        .map(|id| {
-            let instance = departure_time / id;
-            (id, id * (instance + 1))
-        })
-        .min_by_key(|&(_id, next_departure)| next_departure)
-        .ok_or(Errors::NoSolutionFound)?;
+/// Bus notes, the earliest departure time and the bus ids where `None` is an out of service bus
+pub struct Notes {
+    departure_time: u64,
+    bus_ids: Vec<Option<u64>>,
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input<'i> = Notes;
+    type Answer1 = u64;
+    type Answer2 = i64;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(earliest_bus(input.departure_time, &input.bus_ids)?)
+    }
 
-    Ok((next_departure - departure_time) * next_id)
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(earliest_alignment(&input.bus_ids)?)
+    }
 }
 
-pub fn challenge2(input: &str) -> Result<i64, Errors> {
-    // Vec<(remainder, modulus)>
-    let constraints: Vec<(i64, i64)> = input
+fn parse(input: &str) -> Result<Notes, Diagnostic> {
+    let mut lines = input.lines();
+    let line = lines
+        .next()
+        .ok_or_else(|| Diagnostic::end_of_input(input, Errors::InvalidInput))?;
+    let departure_time = line
+        .parse()
+        .map_err(|e| Diagnostic::new(line, line, Errors::from(e)))?;
+    let bus_ids = parse_bus_ids(input)?;
+
+    Ok(Notes {
+        departure_time,
+        bus_ids,
+    })
+}
+
+/// Bus ids from the second line of the notes
+fn parse_bus_ids(input: &str) -> Result<Vec<Option<u64>>, Diagnostic> {
+    let line = input
         .lines()
         .nth(1)
-        .ok_or(Errors::InvalidInput)?
-        .split(',')
-        .enumerate()
-        .filter_map(|(index, s)| {
-            // We want to find a number where items have a remainder of (modulus - index)
-            // - `N % modulus[0] == 0`
-            // - `(N + 1) % modulus[1] == 0` => `N % modulus[1] == modulus[1] - 1`
-            // - ...
-            (s != "x").then(|| {
-                s.parse()
-                    .map(|modulus: i64| (modulus - index as i64, modulus))
-            })
+        .ok_or_else(|| Diagnostic::end_of_input(input, Errors::InvalidInput))?;
+
+    line.split(',')
+        .map(|s| {
+            let id = (s != "x")
+                .then(|| s.parse())
+                .transpose()
+                .map_err(Errors::from)
+                .and_then(|id| match id {
+                    Some(0) => Err(Errors::ZeroBusId),
+                    id => Ok(id),
+                });
+
+            id.map_err(|e| Diagnostic::new(line, s, e).on_line(2))
         })
-        .try_collect()?;
+        .collect()
+}
 
-    // The product of the modulus is the repeat rate of the alignment
-    let product: i64 = constraints.iter().map(|&(_, m)| m).product();
-    let sum: i64 = constraints
+pub fn challenge1(input: &str) -> Result<u64, Errors> {
+    let notes = parse(input)?;
+    earliest_bus(notes.departure_time, &notes.bus_ids)
+}
+
+fn earliest_bus(departure_time: u64, bus_ids: &[Option<u64>]) -> Result<u64, Errors> {
+    let mut earliest: Option<(u64, u64)> = None;
+    for &id in bus_ids.iter().flatten() {
+        let next_departure = (departure_time / id + 1)
+            .checked_mul(id)
+            .ok_or(Errors::Overflow)?;
+        if earliest.is_none_or(|(_, earliest)| next_departure < earliest) {
+            earliest = Some((id, next_departure));
+        }
+    }
+    let (next_id, next_departure) = earliest.ok_or(Errors::NoSolutionFound)?;
+
+    (next_departure - departure_time)
+        .checked_mul(next_id)
+        .ok_or(Errors::Overflow)
+}
+
+pub fn challenge2(input: &str) -> Result<i64, Errors> {
+    earliest_alignment(&parse_bus_ids(input)?)
+}
+
+fn earliest_alignment(bus_ids: &[Option<u64>]) -> Result<i64, Errors> {
+    // Bus `index` must leave `index` minutes after the alignment `N`:
+    // - `N % id[0] == 0`
+    // - `(N + 1) % id[1] == 0` => `N ≡ -1 (mod id[1])`
+    // - ...
+    let congruences = bus_ids
         .iter()
-        .map(|&(remainder, modulus)| {
-            // Find a factor that will hide this contribution from the other modulus
-            let other_mods = product / modulus;
-
-            // Find a factor that will produce a remainder of 1 with this modulus `other_mods * inv % modulus == 1`
-            let inv = (1..)
-                .filter(|i| i * other_mods % modulus == 1)
-                .next()
-                .unwrap();
-
-            // `other_mods * inv % modulus == 1` so multiplying by our remainder carries it into the result
-            remainder * other_mods * inv
-        })
-        .sum();
+        .enumerate()
+        .filter_map(|(index, id)| id.map(|id| (-(index as i128), i128::from(id))));
+    let (alignment, _) = modular::chinese_remainder(congruences)?;
 
-    Ok(sum % product)
+    i64::try_from(alignment).map_err(|_| Errors::Overflow)
 }
 
 #[cfg(test)]
@@ -98,6 +151,48 @@ mod tests {
         assert_eq!(super::challenge2("---\n67,x,7,59,61")?, 779210);
         assert_eq!(super::challenge2("---\n67,7,x,59,61")?, 1261476);
         assert_eq!(super::challenge2("---\n1789,37,47,1889")?, 1202161486);
+        assert_eq!(super::challenge2("---\n4,x,6")?, 4);
+        assert!(matches!(
+            super::challenge2("---\n4,6"),
+            Err(super::Errors::NoAlignment(super::ModularError::NoSolution))
+        ));
         Ok(())
     }
+
+    #[test]
+    fn test_invalid_notes() {
+        match super::challenge1("939\n7,13,y,x") {
+            Err(super::Errors::Invalid(diagnostic)) => {
+                assert_eq!((diagnostic.line(), diagnostic.columns()), (2, 6..7));
+            }
+            _ => panic!("Invalid bus id was accepted"),
+        }
+        match super::challenge1("939") {
+            Err(super::Errors::Invalid(diagnostic)) => assert_eq!(diagnostic.line(), 2),
+            _ => panic!("Missing bus ids were accepted"),
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_planted(planted in generators::day13::bus_notes()) {
+            proptest::prop_assert_eq!(super::challenge1(&planted.input)?, planted.part1);
+            proptest::prop_assert_eq!(super::challenge2(&planted.input)?, planted.part2);
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_reference(input in generators::day13::small_schedules()) {
+            let alignment = super::challenge2(&input);
+            match super::reference::challenge2(&input) {
+                Ok(expected) => proptest::prop_assert_eq!(alignment?, expected),
+                Err(_) => proptest::prop_assert!(
+                    matches!(alignment, Err(super::Errors::NoAlignment(_))),
+                    "Buses never align but got {:?}",
+                    alignment
+                ),
+            }
+        }
+    }
 }
@@ -0,0 +1,42 @@
+//! Brute force solvers, slow but simple enough to check the fast ones against
+
+use std::convert::TryFrom;
+
+use crate::{parse_bus_ids, Errors};
+
+/// [`challenge2`](crate::challenge2) trying each departure of the bus with the largest id in
+/// turn, until every other bus leaves right on time
+///
+/// The buses are all back in the same position after the product of their ids, so the search
+/// stops there. That is up to the product divided by the largest id tries, only practical for
+/// small ids.
+pub fn challenge2(input: &str) -> Result<i64, Errors> {
+    let buses: Vec<(u64, u64)> = parse_bus_ids(input)?
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, id)| id.map(|id| (index as u64, id)))
+        .collect();
+    let bound = buses
+        .iter()
+        .try_fold(1_u64, |product, &(_, id)| product.checked_mul(id))
+        .ok_or(Errors::Overflow)?;
+    let (offset, step) = buses
+        .iter()
+        .copied()
+        .max_by_key(|&(_, id)| id)
+        .unwrap_or((0, 1));
+
+    // The largest bus leaves `offset` minutes after the first candidate
+    let first = (step - offset % step) % step;
+    let step = usize::try_from(step).map_err(|_| Errors::Overflow)?;
+    let alignment = (first..bound)
+        .step_by(step)
+        .find(|&time| {
+            buses
+                .iter()
+                .all(|&(index, id)| (u128::from(time) + u128::from(index)) % u128::from(id) == 0)
+        })
+        .ok_or(Errors::NoSolutionFound)?;
+
+    i64::try_from(alignment).map_err(|_| Errors::Overflow)
+}
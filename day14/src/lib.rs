@@ -1,37 +1,56 @@
-#![allow(unstable_name_collisions)]
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::HashMap, io::BufRead, str::FromStr};
 
 use displaydoc::Display;
 use regex::Regex;
 use thiserror::Error;
+use utils::{Diagnostic, ReadError, Solution};
 
 #[derive(Debug, Error, Display)]
 pub enum Errors {
+    /// Invalid input: {0}
+    Invalid(#[from] Diagnostic),
+    /// {0}
+    Read(#[from] ReadError),
     /// Parse error {0}
     ParseError(#[from] std::num::ParseIntError),
-    /// Could not parse {0}
-    InvalidLine(String),
+    /// Expected "mask = <bits>" or "mem[<address>] = <value>"
+    InvalidLine,
     /// Bad regex {0}
     BadRegex(#[from] regex::Error),
+    /// Mask is {0} bits long, expected at most 36
+    MaskTooLong(usize),
+    /// Mask has {0} floating bits, the address decoder supports at most 12
+    TooManyFloating(u32),
+    /// Sum of the memory does not fit in 64 bits
+    Overflow,
 }
 
-enum Instruction {
+/// Initialization program instruction
+#[derive(Debug, Clone, Copy)]
+pub enum Instruction {
     Mask { clear: u64, float: u64, set: u64 },
     Assign { address: u64, value: u64 },
 }
 
 impl FromStr for Instruction {
-    type Err = Errors;
+    type Err = Diagnostic;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mask = Regex::new(r#"^mask = (?P<mask>[X10]+)$"#)?;
-        let assign = Regex::new(r#"^mem\[(?P<address>\d+)\] = (?P<value>\d+)$"#)?;
+        let regex =
+            |pattern| Regex::new(pattern).map_err(|e| Diagnostic::new(s, s, Errors::from(e)));
+        let mask = regex(r#"^mask = (?P<mask>[X10]+)$"#)?;
+        let assign = regex(r#"^mem\[(?P<address>\d+)\] = (?P<value>\d+)$"#)?;
         if let Some(captures) = mask.captures(s) {
+            let bits = &captures["mask"];
+            if bits.len() > 36 {
+                return Err(Diagnostic::new(s, bits, Errors::MaskTooLong(bits.len())));
+            }
+
             let mut set = 0;
             let mut clear = 0;
             let mut float = 0;
 
-            for c in captures["mask"].chars() {
+            for c in bits.chars() {
                 set <<= 1;
                 clear <<= 1;
                 float <<= 1;
@@ -45,24 +64,61 @@ impl FromStr for Instruction {
 
             Ok(Self::Mask { clear, float, set })
         } else if let Some(captures) = assign.captures(s) {
-            let address = captures["address"].parse()?;
-            let value = captures["value"].parse()?;
-            Ok(Self::Assign { address, value })
+            let number = |name: &str| {
+                let token = &captures[name];
+                token
+                    .parse()
+                    .map_err(|e| Diagnostic::new(s, token, Errors::from(e)))
+            };
+            Ok(Self::Assign {
+                address: number("address")?,
+                value: number("value")?,
+            })
         } else {
-            Err(Errors::InvalidLine(s.to_string()))
+            Err(Diagnostic::new(s, s, Errors::InvalidLine))
         }
     }
 }
 
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input<'i> = Vec<Instruction>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(run_value_decoder(input.iter().copied().map(Ok))?)
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(run_address_decoder(input.iter().copied().map(Ok))?)
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<Instruction>, Diagnostic> {
+    utils::parse_lines(input, str::parse)
+}
+
 pub fn challenge1(input: &str) -> Result<u64, Errors> {
+    run_value_decoder(parse(input)?.into_iter().map(Ok))
+}
+
+/// Run the program with the mask applied to the values, returns the sum of the memory
+fn run_value_decoder<I>(program: I) -> Result<u64, Errors>
+where
+    I: IntoIterator<Item = Result<Instruction, Errors>>,
+{
     let mut mem = HashMap::new();
     let mut set_mask = 0;
     let mut clear_mask = 0;
 
-    for line in input.lines() {
-        let instruction: Instruction = line.parse()?;
-
-        match instruction {
+    for instruction in program {
+        match instruction? {
             Instruction::Mask { set, clear, .. } => {
                 set_mask = set;
                 clear_mask = clear;
@@ -72,19 +128,34 @@ pub fn challenge1(input: &str) -> Result<u64, Errors> {
             }
         }
     }
-    Ok(mem.values().sum())
+    memory_sum(&mem)
 }
 
 pub fn challenge2(input: &str) -> Result<u64, Errors> {
+    run_address_decoder(parse(input)?.into_iter().map(Ok))
+}
+
+/// Floating bits the address decoder accepts in a mask, each write goes to `2^n` addresses
+const MAX_FLOATING: u32 = 12;
+
+/// Run the program with the mask applied to the addresses, returns the sum of the memory
+///
+/// Fails with [`Errors::TooManyFloating`] on a mask with more than [`MAX_FLOATING`] floating bits,
+/// rather than writing to as many as `2^36` addresses for each assignment.
+fn run_address_decoder<I>(program: I) -> Result<u64, Errors>
+where
+    I: IntoIterator<Item = Result<Instruction, Errors>>,
+{
     let mut mem = HashMap::new();
     let mut float_mask = 0;
     let mut set_mask = 0;
 
-    for line in input.lines() {
-        let instruction: Instruction = line.parse()?;
-
-        match instruction {
+    for instruction in program {
+        match instruction? {
             Instruction::Mask { set, float, .. } => {
+                if float.count_ones() > MAX_FLOATING {
+                    return Err(Errors::TooManyFloating(float.count_ones()));
+                }
                 set_mask = set;
                 float_mask = float;
             }
@@ -96,7 +167,24 @@ pub fn challenge2(input: &str) -> Result<u64, Errors> {
             }
         }
     }
-    Ok(mem.values().sum())
+    memory_sum(&mem)
+}
+
+/// [`challenge1`] reading one instruction at a time, only the memory is kept
+pub fn challenge1_from_reader(reader: impl BufRead) -> Result<u64, Errors> {
+    run_value_decoder(utils::read_lines(reader, str::parse).map(|i| Ok(i?)))
+}
+
+/// [`challenge2`] reading one instruction at a time, only the memory is kept
+pub fn challenge2_from_reader(reader: impl BufRead) -> Result<u64, Errors> {
+    run_address_decoder(utils::read_lines(reader, str::parse).map(|i| Ok(i?)))
+}
+
+/// Sum of the values in memory
+fn memory_sum(mem: &HashMap<u64, u64>) -> Result<u64, Errors> {
+    mem.values()
+        .try_fold(0_u64, |sum, &value| sum.checked_add(value))
+        .ok_or(Errors::Overflow)
 }
 
 pub fn float_mask_options(base: u64, mask: u64) -> impl Iterator<Item = u64> {
@@ -105,10 +193,10 @@ pub fn float_mask_options(base: u64, mask: u64) -> impl Iterator<Item = u64> {
         .map(|b| 1 << b)
         .filter(|base_mask| mask & base_mask != 0)
         .enumerate()
-        .map(|(index, base_mask)| (1 << index, base_mask))
+        .map(|(index, base_mask)| (1_u64 << index, base_mask))
         .collect();
 
-    (0..1 << bit_mappings.len()).map(move |permutation| {
+    (0..1_u64 << bit_mappings.len()).map(move |permutation| {
         bit_mappings
             .iter()
             .fold(base, |value, (permutation_mask, base_mask)| {
@@ -131,6 +219,7 @@ mem[8] = 11
 mem[7] = 101
 mem[8] = 0"#;
         assert_eq!(super::challenge1(INPUT)?, 165);
+        assert_eq!(super::challenge1_from_reader(INPUT.as_bytes())?, 165);
         Ok(())
     }
 
@@ -141,6 +230,14 @@ mem[42] = 100
 mask = 00000000000000000000000000000000X0XX
 mem[26] = 1"#;
         assert_eq!(super::challenge2(INPUT)?, 208);
+        assert_eq!(super::challenge2_from_reader(INPUT.as_bytes())?, 208);
+
+        let floating = "mask = XXXXXXXXXXXXX\nmem[1] = 1";
+        assert_eq!(super::challenge1(floating)?, 1);
+        assert!(matches!(
+            super::challenge2(floating),
+            Err(super::Errors::TooManyFloating(13))
+        ));
         Ok(())
     }
 }
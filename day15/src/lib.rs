@@ -1,44 +1,91 @@
 use displaydoc::Display;
-use itertools::Itertools as _;
 use std::collections::HashMap;
 use thiserror::Error;
+use utils::{Diagnostic, Solution};
 
 #[derive(Debug, Error, Display)]
 pub enum Errors {
+    /// Invalid input: {0}
+    Invalid(#[from] Diagnostic),
     /// Parse error {0}
     ParseError(#[from] std::num::ParseIntError),
 }
 
+/// Turns between two progress events
+const PROGRESS_TURNS: usize = 1 << 20;
+
+#[tracing::instrument(level = "debug", skip(starting_numbers))]
 pub fn game(mut starting_numbers: Vec<(u64, usize)>, nth: usize) -> u64 {
     let (last, last_index) = starting_numbers.pop().unwrap_or((0, 0));
     let mut spoken: HashMap<u64, usize> = starting_numbers.into_iter().collect();
 
-    (last_index..nth - 1).fold(last, |last, index| {
+    (last_index..nth.saturating_sub(1)).fold(last, |last, index| {
+        if index.is_multiple_of(PROGRESS_TURNS) {
+            tracing::debug!(turn = index + 1, spoken = spoken.len());
+        }
         spoken
             .insert(last, index)
-            .map(|last_spoken| (index - last_spoken) as u64)
+            .map(|last_spoken| index.saturating_sub(last_spoken) as u64)
             .unwrap_or(0)
     })
 }
 
-pub fn challenge1(input: &str) -> Result<u64, Errors> {
-    let list: Vec<(u64, usize)> = input
-        .split(',')
-        .enumerate()
-        .map(|(index, num)| num.parse::<u64>().map(|num| (num, index)))
-        .try_collect()?;
+/// Memory game, each part asks for the number spoken on a turn
+///
+/// The game plays every turn up to the one asked for, whatever the starting numbers, so the turns
+/// bound the time it takes and the numbers remembered: the 30 million turns of the second part
+/// take seconds.
+pub struct Day15 {
+    pub turn1: usize,
+    pub turn2: usize,
+}
 
-    Ok(game(list, 2020))
+impl Default for Day15 {
+    fn default() -> Self {
+        Self {
+            turn1: 2020,
+            turn2: 30000000,
+        }
+    }
 }
 
-pub fn challenge2(input: &str) -> Result<u64, Errors> {
-    let list: Vec<(u64, usize)> = input
-        .split(',')
+impl Solution for Day15 {
+    type Input<'i> = Vec<(u64, usize)>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(game(input.clone(), self.turn1))
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(game(input.clone(), self.turn2))
+    }
+}
+
+/// Parse the starting numbers along with the turn index they are spoken on
+fn parse(input: &str) -> Result<Vec<(u64, usize)>, Diagnostic> {
+    let line = input.trim_end();
+    line.split(',')
         .enumerate()
-        .map(|(index, num)| num.parse::<u64>().map(|num| (num, index)))
-        .try_collect()?;
+        .map(|(index, num)| {
+            num.parse::<u64>()
+                .map(|num| (num, index))
+                .map_err(|e| Diagnostic::new(line, num, Errors::from(e)))
+        })
+        .collect()
+}
 
-    Ok(game(list, 30000000))
+pub fn challenge1(input: &str) -> Result<u64, Errors> {
+    Ok(game(parse(input)?, 2020))
+}
+
+pub fn challenge2(input: &str) -> Result<u64, Errors> {
+    Ok(game(parse(input)?, 30000000))
 }
 
 #[cfg(test)]
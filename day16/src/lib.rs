@@ -1,33 +1,40 @@
 use displaydoc::Display;
 use itertools::Itertools as _;
-use std::{
-    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
-    convert::TryFrom,
-    str::FromStr,
-};
+use std::{convert::TryFrom, str::FromStr};
 use thiserror::Error;
+use utils::{Diagnostic, Record, Solution};
+
+#[cfg(any(test, feature = "reference"))]
+pub mod reference;
 
 #[derive(Debug, Error, Display)]
 pub enum Errors {
+    /// Invalid input: {0}
+    Invalid(#[from] Diagnostic),
     /// Parse error {0}
     ParseError(#[from] std::num::ParseIntError),
     /// Regex parse error {0}
     RegexError(#[from] regex::Error),
     /// No solution found
     NoSolution,
-    /// Input not of the expected format
+    /// Expected the field rules, your ticket and the nearby tickets separated by blank lines
     BadInput,
-    /// Line not of the expected format
-    BadLine(String),
+    /// Expected a field rule like "class: 1-3 or 5-7"
+    BadLine,
     /// Range format invalid
     BadRange,
+    /// Ticket has {values} values but there are {fields} fields
+    TicketLength { values: usize, fields: usize },
+    /// Answer does not fit in 64 bits
+    Overflow,
 }
 
+/// Inclusive range of valid values
 #[derive(Debug, Clone)]
-struct Range(u64, u64);
+pub struct Range(u64, u64);
 impl Range {
     pub fn contains(&self, value: u64) -> bool {
-        self.0 <= value && value <= self.1
+        (self.0..=self.1).contains(&value)
     }
 }
 
@@ -40,8 +47,9 @@ impl FromStr for Range {
     }
 }
 
+/// Ticket field rule, the field name and the two ranges its value must fall in
 #[derive(Debug, Clone)]
-struct Constraint<'t> {
+pub struct Constraint<'t> {
     pub name: &'t str,
     pub r1: Range,
     pub r2: Range,
@@ -54,55 +62,137 @@ impl Constraint<'_> {
 }
 
 impl<'t> TryFrom<&'t str> for Constraint<'t> {
-    type Error = Errors;
+    type Error = Diagnostic;
 
     fn try_from(line: &'t str) -> Result<Self, Self::Error> {
-        let c_re = regex::Regex::new(r#"^(.+): (\d+-\d+) or (\d+-\d+)$"#)?;
+        let c_re = regex::Regex::new(r#"^(.+): (\d+-\d+) or (\d+-\d+)$"#)
+            .map_err(|e| Diagnostic::new(line, line, Errors::from(e)))?;
         let captures = c_re
             .captures(line)
-            .ok_or(Errors::BadLine(line.to_string()))?;
+            .ok_or_else(|| Diagnostic::new(line, line, Errors::BadLine))?;
+        let range = |index| {
+            let token = captures.get(index).map_or("", |m| m.as_str());
+            token.parse().map_err(|e| Diagnostic::new(line, token, e))
+        };
+
+        Ok(Self {
+            name: captures.get(1).map_or("", |m| m.as_str()),
+            r1: range(2)?,
+            r2: range(3)?,
+        })
+    }
+}
 
-        let name = captures.get(1).unwrap().as_str();
-        let r1 = captures[2].parse()?;
-        let r2 = captures[3].parse()?;
+/// Ticket notes, the field rules, your ticket and the nearby tickets
+pub struct Notes<'t> {
+    constraints: Vec<Constraint<'t>>,
+    mine: Vec<u64>,
+    nearby: Vec<Vec<u64>>,
+}
 
-        Ok(Self { name, r1, r2 })
+/// Ticket translation, the second challenge multiplies the fields of your ticket starting with `key`
+pub struct Day16 {
+    pub key: &'static str,
+}
+
+impl Default for Day16 {
+    fn default() -> Self {
+        Self { key: "departure" }
     }
 }
 
-fn parse(input: &str) -> Result<(Vec<Constraint>, Vec<u64>, Vec<Vec<u64>>), Errors> {
-    let (constraints, my_ticket, nearby_tickets) = input
-        .split("\n\n")
-        .collect_tuple()
-        .ok_or(Errors::BadInput)?;
+impl Solution for Day16 {
+    type Input<'i> = Notes<'i>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(error_rate(input)?)
+    }
 
-    let constraints = constraints
-        .lines()
-        .map(Constraint::try_from)
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(field_product(input, self.key)?)
+    }
+}
+
+fn parse(input: &str) -> Result<Notes<'_>, Diagnostic> {
+    let records: Vec<Record> = utils::records(input).collect();
+    let (constraints, my_ticket, nearby_tickets) = match &records[..] {
+        [constraints, my_ticket, nearby_tickets] => (constraints, my_ticket, nearby_tickets),
+        [_, _, _, extra, ..] => {
+            let line = extra.lines[0];
+            return Err(Diagnostic::new(line, line, Errors::BadInput).on_line(extra.line));
+        }
+        _ => return Err(Diagnostic::end_of_input(input, Errors::BadInput)),
+    };
+
+    let constraints: Vec<Constraint> = constraints
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            Constraint::try_from(*line).map_err(|e| e.on_line(constraints.line + index))
+        })
         .try_collect()?;
 
-    let mine: Vec<u64> = my_ticket
-        .lines()
-        .skip(1)
+    let mine = parse_tickets(my_ticket, constraints.len())?
+        .into_iter()
         .next()
-        .ok_or(Errors::BadInput)?
-        .split(',')
-        .map(str::parse)
-        .try_collect()?;
+        .ok_or_else(|| Diagnostic::new("", "", Errors::BadInput).on_line(my_ticket.line + 1))?;
+    let nearby = parse_tickets(nearby_tickets, constraints.len())?;
+
+    Ok(Notes {
+        constraints,
+        mine,
+        nearby,
+    })
+}
 
-    let nearby: Vec<Vec<u64>> = nearby_tickets
-        .lines()
+/// Comma separated ticket values of the lines following the heading of the record, one per field
+fn parse_tickets(record: &Record<'_>, fields: usize) -> Result<Vec<Vec<u64>>, Diagnostic> {
+    record
+        .lines
+        .iter()
+        .enumerate()
         .skip(1)
-        .map(|line| line.split(',').map(str::parse).try_collect())
-        .try_collect()?;
-
-    Ok((constraints, mine, nearby))
+        .map(|(index, line)| {
+            let diagnostic =
+                |token, error| Diagnostic::new(line, token, error).on_line(record.line + index);
+            let values: Vec<u64> = line
+                .split(',')
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|e| diagnostic(value, Errors::from(e)))
+                })
+                .try_collect()?;
+
+            if values.len() != fields {
+                let values = values.len();
+                return Err(diagnostic(line, Errors::TicketLength { values, fields }));
+            }
+            Ok(values)
+        })
+        .collect()
 }
 
 pub fn challenge1(input: &str) -> Result<u64, Errors> {
-    let (constraints, _, nearby) = parse(input)?;
+    error_rate(&parse(input)?)
+}
+
+/// Sum of the nearby ticket values that are not valid for any field
+fn error_rate(notes: &Notes<'_>) -> Result<u64, Errors> {
+    let Notes {
+        constraints,
+        nearby,
+        ..
+    } = notes;
 
-    let sum = nearby
+    nearby
         .iter()
         .flat_map(|c| c.iter())
         .filter(|&&n| {
@@ -110,19 +200,23 @@ pub fn challenge1(input: &str) -> Result<u64, Errors> {
 
             !valid
         })
-        .sum();
-
-    Ok(sum)
+        .try_fold(0_u64, |sum, &n| sum.checked_add(n))
+        .ok_or(Errors::Overflow)
 }
 
 pub fn challenge2(input: &str, key: &str) -> Result<u64, Errors> {
-    let (constraints, mine, nearby) = parse(input)?;
-    let mut possible_fields: Vec<Vec<Constraint>> = nearby
-        .get(0)
-        .ok_or(Errors::NoSolution)?
-        .iter()
-        .map(|_| constraints.clone())
-        .collect();
+    field_product(&parse(input)?, key)
+}
+
+/// Product of the values of your ticket for the fields starting with `key`
+fn field_product(notes: &Notes<'_>, key: &str) -> Result<u64, Errors> {
+    let Notes {
+        constraints,
+        mine,
+        nearby,
+    } = notes;
+    let mut possible_fields: Vec<Vec<&Constraint>> =
+        mine.iter().map(|_| constraints.iter().collect()).collect();
 
     nearby
         .iter()
@@ -150,15 +244,24 @@ pub fn challenge2(input: &str, key: &str) -> Result<u64, Errors> {
         }
     }
 
+    // Positions left with the same single field contradict each other
+    let assigned = possible_fields
+        .iter()
+        .filter_map(|f| f.first())
+        .map(|c| c.name);
+    if assigned.unique().count() != possible_fields.len() {
+        return Err(Errors::NoSolution);
+    }
+
     let mut product = 1;
 
-    for (constraints, mine) in possible_fields.into_iter().zip(mine.into_iter()) {
+    for (constraints, mine) in possible_fields.into_iter().zip(mine) {
         if constraints.len() != 1 {
             return Err(Errors::NoSolution);
         }
 
         if constraints[0].name.starts_with(key) {
-            product *= mine;
+            product = mine.checked_mul(product).ok_or(Errors::Overflow)?;
         }
     }
 
@@ -202,6 +305,28 @@ nearby tickets:
         assert_eq!(super::challenge2(INPUT, "s")?, 13);
         assert_eq!(super::challenge2(INPUT, "ro")?, 11);
         assert_eq!(super::challenge2(INPUT, "class")?, 12);
+
+        let crlf = INPUT.replace('\n', "\r\n");
+        assert_eq!(super::challenge2(&crlf, "s")?, 13);
+
+        const CONFLICT: &str =
+            "a: 1-2 or 8-9\nb: 3-4 or 5-6\n\nyour ticket:\n1,1\n\nnearby tickets:\n3,5";
+        assert!(matches!(
+            super::challenge2(CONFLICT, "a"),
+            Err(super::Errors::NoSolution)
+        ));
         Ok(())
     }
+
+    proptest::proptest! {
+        #[test]
+        fn test_reference(input in generators::day16::ticket_notes()) {
+            let product = super::challenge2(&input, "departure");
+            proptest::prop_assert!(product.is_ok(), "Fast solver failed: {:?}", product);
+            proptest::prop_assert_eq!(
+                product.ok(),
+                super::reference::challenge2(&input, "departure").ok()
+            );
+        }
+    }
 }
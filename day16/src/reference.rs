@@ -0,0 +1,48 @@
+//! Brute force solvers, slow but simple enough to check the fast ones against
+
+use itertools::Itertools as _;
+
+use crate::{parse, Errors, Notes};
+
+/// [`challenge2`](crate::challenge2) trying every assignment of the fields to the ticket
+/// positions, only practical for a handful of fields as there are factorially many
+///
+/// Exactly one assignment must agree with every valid nearby ticket.
+pub fn challenge2(input: &str, key: &str) -> Result<u64, Errors> {
+    let Notes {
+        constraints,
+        mine,
+        nearby,
+    } = parse(input)?;
+    let valid: Vec<&Vec<u64>> = nearby
+        .iter()
+        .filter(|ticket| {
+            ticket
+                .iter()
+                .all(|&value| constraints.iter().any(|c| c.is_valid(value)))
+        })
+        .collect();
+
+    let mut assignments = constraints
+        .iter()
+        .permutations(constraints.len())
+        .filter(|fields| {
+            valid.iter().all(|ticket| {
+                ticket
+                    .iter()
+                    .zip(fields)
+                    .all(|(&value, field)| field.is_valid(value))
+            })
+        });
+    let fields = assignments.next().ok_or(Errors::NoSolution)?;
+    if assignments.next().is_some() {
+        return Err(Errors::NoSolution);
+    }
+
+    fields
+        .iter()
+        .zip(&mine)
+        .filter(|(field, _)| field.name.starts_with(key))
+        .try_fold(1_u64, |product, (_, &value)| product.checked_mul(value))
+        .ok_or(Errors::Overflow)
+}
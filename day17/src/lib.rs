@@ -1,6 +1,39 @@
+use displaydoc::Display;
 use itertools::Itertools as _;
-use std::collections::HashSet;
-use std::num::ParseIntError;
+use std::{collections::HashSet, convert::TryFrom};
+use thiserror::Error;
+use utils::{Diagnostic, Grid, Solution};
+
+#[derive(Debug, Error, Display)]
+pub enum Errors {
+    /// Invalid initial state: {0}
+    InvalidState(#[from] Diagnostic),
+    /// Booting would go over more than 131072 cubes of the pocket dimension
+    TooLarge,
+}
+
+/// Invalid character {0:?}, expected '.' or '#'
+#[derive(Debug, Error, Display)]
+pub struct InvalidCube(char);
+
+/// Cube of the initial slice of the pocket dimension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cube {
+    Active,
+    Inactive,
+}
+
+impl TryFrom<char> for Cube {
+    type Error = InvalidCube;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '#' => Ok(Cube::Active),
+            '.' => Ok(Cube::Inactive),
+            _ => Err(InvalidCube(value)),
+        }
+    }
+}
 
 #[derive(Debug, Default, Clone, Copy)]
 struct Bound {
@@ -30,38 +63,31 @@ fn active_neighbor_count(coordinate: &[isize], active: &HashSet<Coordinate>) ->
         .count()
 }
 
-fn parse(
-    input: &str,
-    dimensions: usize,
-) -> Result<(Vec<Bound>, HashSet<Coordinate>), ParseIntError> {
+/// Active coordinates of the initial slice extended to `dimensions`, along with their bounds
+fn initial_state(slice: &Grid<Cube>, dimensions: usize) -> (Vec<Bound>, HashSet<Coordinate>) {
     let mut bounds = vec![Bound::default(); dimensions];
 
-    let active: HashSet<Coordinate> = input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.chars()
-                .enumerate()
-                .filter(|&(_, c)| c == '#')
-                .map(move |(x, _)| vec![x as isize, y as isize])
-        })
+    let active: HashSet<Coordinate> = slice
+        .iter()
+        .filter(|&(_, &cube)| cube == Cube::Active)
+        .map(|((y, x), _)| vec![x as isize, y as isize])
         .map(|mut v| {
             v.iter()
                 .zip(bounds.iter_mut())
                 .for_each(|(value, bound)| bound.extend(*value));
 
-            v.extend(std::iter::repeat(0).take(bounds.len() - v.len()));
+            v.resize(bounds.len(), 0);
             v
         })
         .collect();
 
-    Ok((bounds, active))
+    (bounds, active)
 }
 
-fn simulate_cycle(bounds: &mut Vec<Bound>, active: &mut HashSet<Vec<isize>>) {
+fn simulate_cycle(bounds: &mut [Bound], active: &mut HashSet<Vec<isize>>) {
     let inactivated: Vec<Coordinate> = active
         .iter()
-        .filter(|&position| !matches!(active_neighbor_count(position, &active), 2 | 3))
+        .filter(|&position| !matches!(active_neighbor_count(position, active), 2 | 3))
         .cloned()
         .collect();
 
@@ -70,7 +96,7 @@ fn simulate_cycle(bounds: &mut Vec<Bound>, active: &mut HashSet<Vec<isize>>) {
         .map(|b| b.min - 1..=b.max + 1)
         .multi_cartesian_product()
         .filter(|position| !active.contains(position))
-        .filter(|position| active_neighbor_count(position, &active) == 3)
+        .filter(|position| active_neighbor_count(position, active) == 3)
         .collect();
 
     active.extend(activated.into_iter().inspect(|v| {
@@ -84,24 +110,77 @@ fn simulate_cycle(bounds: &mut Vec<Bound>, active: &mut HashSet<Vec<isize>>) {
     }
 }
 
-pub fn challenge1(input: &str) -> Result<usize, ParseIntError> {
-    let (mut bounds, mut active) = parse(input, 3)?;
+/// Cycles of the boot process in the puzzle
+const BOOT_CYCLES: usize = 6;
+
+/// Cubes the boot may go over, about twice the puzzle's in 4 dimensions
+const MAX_CUBES: usize = 1 << 17;
+
+/// Boot the pocket dimension for some cycles, returns the number of active cubes
+///
+/// Each cycle looks at every cube of the box around the active ones, which grows by one cube on
+/// each side, so this fails with [`Errors::TooLarge`] when the box would end up with more than
+/// [`MAX_CUBES`] cubes.
+#[tracing::instrument(level = "debug", skip(slice))]
+fn boot(slice: &Grid<Cube>, dimensions: usize, cycles: usize) -> Result<usize, Errors> {
+    let growth = cycles.checked_mul(2).ok_or(Errors::TooLarge)?;
+    [slice.width(), slice.height()]
+        .iter()
+        .copied()
+        .chain(std::iter::repeat(1))
+        .take(dimensions)
+        .map(|extent| extent.checked_add(growth))
+        .try_fold(1_usize, |cubes, extent| cubes.checked_mul(extent?))
+        .filter(|&cubes| cubes <= MAX_CUBES)
+        .ok_or(Errors::TooLarge)?;
 
-    for _ in 0..6 {
+    let (mut bounds, mut active) = initial_state(slice, dimensions);
+
+    for cycle in 1..=cycles {
         simulate_cycle(&mut bounds, &mut active);
+        tracing::debug!(cycle, active = active.len());
     }
 
     Ok(active.len())
 }
 
-pub fn challenge2(input: &str) -> Result<usize, ParseIntError> {
-    let (mut bounds, mut active) = parse(input, 4)?;
+/// Conway Cubes, the pocket dimension boots for `cycles` cycles
+pub struct Day17 {
+    pub cycles: usize,
+}
 
-    for _ in 0..6 {
-        simulate_cycle(&mut bounds, &mut active);
+impl Default for Day17 {
+    fn default() -> Self {
+        Self {
+            cycles: BOOT_CYCLES,
+        }
     }
+}
 
-    Ok(active.len())
+impl Solution for Day17 {
+    type Input<'i> = Grid<Cube>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(input.parse::<Grid<Cube>>()?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(boot(input, 3, self.cycles)?)
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(boot(input, 4, self.cycles)?)
+    }
+}
+
+pub fn challenge1(input: &str) -> Result<usize, Errors> {
+    boot(&input.parse()?, 3, BOOT_CYCLES)
+}
+
+pub fn challenge2(input: &str) -> Result<usize, Errors> {
+    boot(&input.parse()?, 4, BOOT_CYCLES)
 }
 
 #[cfg(test)]
@@ -111,14 +190,26 @@ mod tests {
 ###"#;
 
     #[test]
-    fn test_challenge1() -> Result<(), super::ParseIntError> {
+    fn test_challenge1() -> Result<(), super::Errors> {
         assert_eq!(super::challenge1(INPUT)?, 112);
         Ok(())
     }
 
     #[test]
-    fn test_challenge2() -> Result<(), super::ParseIntError> {
+    fn test_challenge2() -> Result<(), super::Errors> {
         assert_eq!(super::challenge2(INPUT)?, 848);
+
+        // 3 dimensions fit a long row, 4 go past the bound
+        let row = "#".repeat(200);
+        assert!(super::challenge1(&row).is_ok());
+        assert!(matches!(
+            super::challenge2(&row),
+            Err(super::Errors::TooLarge)
+        ));
+        assert!(matches!(
+            super::boot(&INPUT.parse()?, 3, usize::MAX),
+            Err(super::Errors::TooLarge)
+        ));
         Ok(())
     }
 }
@@ -1,77 +1,234 @@
-use std::borrow::Cow;
+use std::{borrow::Borrow, io::BufRead, str::FromStr};
 
 use anyhow::Result;
-use once_cell::sync::Lazy;
-use regex::{Captures, Regex};
+use displaydoc::Display;
+use thiserror::Error;
+use utils::{Diagnostic, ReadError, Solution};
 
-pub fn challenge1(input: &str) -> Result<u64> {
-    Ok(input.lines().map(evaluate_naive).sum())
+#[derive(Debug, Error, Display)]
+pub enum Errors {
+    /// Invalid expression: {0}
+    Invalid(#[from] Diagnostic),
+    /// {0}
+    Read(#[from] ReadError),
+    /// Parse error {0}
+    ParseError(#[from] std::num::ParseIntError),
+    /// Invalid character {0:?}, expected a number, an operator or a parenthesis
+    InvalidCharacter(char),
+    /// Expected a number or '('
+    ExpectedOperand,
+    /// Expected '+', '*' or ')'
+    ExpectedOperator,
+    /// Unmatched ')'
+    UnmatchedParenthesis,
+    /// Unclosed '('
+    UnclosedParenthesis,
+    /// Result does not fit in 64 bits
+    Overflow,
 }
 
-pub fn challenge2(input: &str) -> Result<u64> {
-    Ok(input.lines().map(evaluate_naive2).sum())
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Number(u64),
+    Add,
+    Multiply,
+    Open,
+    Close,
 }
 
-const RE_PAREN_EXPR: Lazy<Regex> = Lazy::new(|| Regex::new("\\(([^()]+)\\)").unwrap());
-const RE_GENERIC_EXPR: Lazy<Regex> = Lazy::new(|| Regex::new("^(\\d+) ([+*]) (\\d+)").unwrap());
-const RE_ADD_EXPR: Lazy<Regex> = Lazy::new(|| Regex::new("(\\d+) \\+ (\\d+)").unwrap());
-const RE_MUL_EXPR: Lazy<Regex> = Lazy::new(|| Regex::new("(\\d+) \\* (\\d+)").unwrap());
+/// Homework expression, numbers and operators alternate and the parentheses are balanced
+#[derive(Debug, Clone)]
+pub struct Expression(Vec<Token>);
+
+impl FromStr for Expression {
+    type Err = Diagnostic;
 
-pub fn evaluate_naive2(expr: &str) -> u64 {
-    let mut expr = Cow::Borrowed(expr);
+    fn from_str(expr: &str) -> Result<Self, Self::Err> {
+        let error = |token, error| Err(Diagnostic::new(expr, token, error));
+        let mut tokens = Vec::new();
+        let mut open = Vec::new();
+        let mut expect_operand = true;
+        let mut chars = expr.char_indices().peekable();
 
-    while let Cow::Owned(str) = RE_PAREN_EXPR.replace(expr.as_ref(), |captures: &Captures| {
-        evaluate_naive2(&captures[1]).to_string()
-    }) {
-        expr = Cow::Owned(str);
+        while let Some((start, c)) = chars.next() {
+            let token = &expr[start..start + c.len_utf8()];
+            match c {
+                ' ' => continue,
+                '0'..='9' if expect_operand => {
+                    let mut end = start + 1;
+                    while let Some((index, '0'..='9')) = chars.peek().copied() {
+                        end = index + 1;
+                        chars.next();
+                    }
+                    let number = &expr[start..end];
+                    match number.parse() {
+                        Ok(number) => tokens.push(Token::Number(number)),
+                        Err(e) => return error(number, Errors::from(e)),
+                    }
+                    expect_operand = false;
+                }
+                '(' if expect_operand => {
+                    open.push(token);
+                    tokens.push(Token::Open);
+                }
+                ')' if !expect_operand => {
+                    if open.pop().is_none() {
+                        return error(token, Errors::UnmatchedParenthesis);
+                    }
+                    tokens.push(Token::Close);
+                }
+                '+' | '*' if !expect_operand => {
+                    expect_operand = true;
+                    tokens.push(if c == '+' {
+                        Token::Add
+                    } else {
+                        Token::Multiply
+                    });
+                }
+                '0'..='9' | '(' => return error(token, Errors::ExpectedOperator),
+                ')' | '+' | '*' => return error(token, Errors::ExpectedOperand),
+                c => return error(token, Errors::InvalidCharacter(c)),
+            }
+        }
+
+        if expect_operand {
+            error(&expr[expr.len()..], Errors::ExpectedOperand)
+        } else if let Some(token) = open.pop() {
+            error(token, Errors::UnclosedParenthesis)
+        } else {
+            Ok(Self(tokens))
+        }
     }
+}
+
+pub struct Day18;
 
-    while let Cow::Owned(str) = RE_ADD_EXPR.replace(expr.as_ref(), |captures: &Captures| {
-        let a: u64 = captures[1].parse().expect("Unable to parse a.");
-        let b: u64 = captures[2].parse().expect("Unable to parse b.");
+impl Solution for Day18 {
+    type Input<'i> = Vec<Expression>;
+    type Answer1 = u64;
+    type Answer2 = u64;
 
-        (a + b).to_string()
-    }) {
-        expr = Cow::Owned(str);
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
     }
 
-    while let Cow::Owned(str) = RE_MUL_EXPR.replace(expr.as_ref(), |captures: &Captures| {
-        let a: u64 = captures[1].parse().expect("Unable to parse a.");
-        let b: u64 = captures[2].parse().expect("Unable to parse b.");
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(sum(input.iter().map(Ok), same_precedence)?)
+    }
 
-        (a * b).to_string()
-    }) {
-        expr = Cow::Owned(str);
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(sum(input.iter().map(Ok), addition_first)?)
     }
+}
+
+fn parse(input: &str) -> Result<Vec<Expression>, Diagnostic> {
+    utils::parse_lines(input, str::parse)
+}
+
+pub fn challenge1(input: &str) -> Result<u64> {
+    Ok(sum(parse(input)?.into_iter().map(Ok), same_precedence)?)
+}
+
+pub fn challenge2(input: &str) -> Result<u64> {
+    Ok(sum(parse(input)?.into_iter().map(Ok), addition_first)?)
+}
+
+/// [`challenge1`] reading one expression at a time
+pub fn challenge1_from_reader(reader: impl BufRead) -> Result<u64> {
+    Ok(sum(read_expressions(reader), same_precedence)?)
+}
+
+/// [`challenge2`] reading one expression at a time
+pub fn challenge2_from_reader(reader: impl BufRead) -> Result<u64> {
+    Ok(sum(read_expressions(reader), addition_first)?)
+}
+
+fn read_expressions(reader: impl BufRead) -> impl Iterator<Item = Result<Expression, Errors>> {
+    utils::read_lines(reader, str::parse).map(|expression| Ok(expression?))
+}
 
-    expr.parse()
-        .expect("Expr had something left in it that was not a number")
+/// Evaluate left to right, ignoring the usual precedence of multiplication
+pub fn evaluate_naive(expr: &str) -> Result<u64, Errors> {
+    evaluate(&expr.parse()?, same_precedence)
 }
 
-pub fn evaluate_naive(expr: &str) -> u64 {
-    let mut expr = Cow::Borrowed(expr);
+/// Evaluate with addition taking precedence over multiplication
+pub fn evaluate_naive2(expr: &str) -> Result<u64, Errors> {
+    evaluate(&expr.parse()?, addition_first)
+}
+
+fn same_precedence(_operator: Token) -> u8 {
+    0
+}
 
-    while let Cow::Owned(str) = RE_PAREN_EXPR.replace(expr.as_ref(), |captures: &Captures| {
-        evaluate_naive(&captures[1]).to_string()
-    }) {
-        expr = Cow::Owned(str);
+fn addition_first(operator: Token) -> u8 {
+    match operator {
+        Token::Add => 1,
+        _ => 0,
     }
+}
+
+/// Sum of the values of the expressions
+fn sum<I, E>(expressions: I, precedence: fn(Token) -> u8) -> Result<u64, Errors>
+where
+    I: IntoIterator<Item = Result<E, Errors>>,
+    E: Borrow<Expression>,
+{
+    expressions.into_iter().try_fold(0_u64, |sum, expression| {
+        sum.checked_add(evaluate(expression?.borrow(), precedence)?)
+            .ok_or(Errors::Overflow)
+    })
+}
 
-    while let Cow::Owned(str) = RE_GENERIC_EXPR.replace(expr.as_ref(), |captures: &Captures| {
-        let a: u64 = captures[1].parse().expect("Unable to parse a.");
-        let b: u64 = captures[3].parse().expect("Unable to parse b.");
+/// Shunting yard evaluation, operators of equal precedence are applied left to right
+fn evaluate(expression: &Expression, precedence: fn(Token) -> u8) -> Result<u64, Errors> {
+    let mut values = Vec::new();
+    let mut operators: Vec<Token> = Vec::new();
 
-        match &captures[2] {
-            "+" => (a + b).to_string(),
-            "*" => (a * b).to_string(),
-            op => panic!("Unknown op {}", op),
+    for &token in expression.0.iter() {
+        match token {
+            Token::Number(number) => values.push(number),
+            Token::Open => operators.push(token),
+            Token::Close => {
+                while let Some(operator) = operators.pop() {
+                    if operator == Token::Open {
+                        break;
+                    }
+                    apply(&mut values, operator)?;
+                }
+            }
+            Token::Add | Token::Multiply => {
+                while let Some(&top) = operators.last() {
+                    if top == Token::Open || precedence(top) < precedence(token) {
+                        break;
+                    }
+                    operators.pop();
+                    apply(&mut values, top)?;
+                }
+                operators.push(token);
+            }
         }
-    }) {
-        expr = Cow::Owned(str);
     }
 
-    expr.parse()
-        .expect("Expr had something left in it that was not a number")
+    while let Some(operator) = operators.pop() {
+        apply(&mut values, operator)?;
+    }
+
+    values.pop().ok_or(Errors::ExpectedOperand)
+}
+
+/// Replace the last two values by the result of the operator
+fn apply(values: &mut Vec<u64>, operator: Token) -> Result<(), Errors> {
+    let b = values.pop().ok_or(Errors::ExpectedOperand)?;
+    let a = values.pop().ok_or(Errors::ExpectedOperand)?;
+    let result = match operator {
+        Token::Add => a.checked_add(b),
+        Token::Multiply => a.checked_mul(b),
+        _ => return Err(Errors::UnclosedParenthesis),
+    };
+
+    values.push(result.ok_or(Errors::Overflow)?);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -87,13 +244,13 @@ mod tests {
     #[test]
     fn test_eval() -> Result<()> {
         use super::evaluate_naive as eval;
-        assert_eq!(eval("1 + 2 * 3 + 4 * 5 + 6"), 71);
-        assert_eq!(eval("1 + (2 * 3) + (4 * (5 + 6))"), 51);
-        assert_eq!(eval("2 * 3 + (4 * 5)"), 26);
-        assert_eq!(eval("5 + (8 * 3 + 9 + 3 * 4 * 3)"), 437);
-        assert_eq!(eval("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))"), 12240);
+        assert_eq!(eval("1 + 2 * 3 + 4 * 5 + 6")?, 71);
+        assert_eq!(eval("1 + (2 * 3) + (4 * (5 + 6))")?, 51);
+        assert_eq!(eval("2 * 3 + (4 * 5)")?, 26);
+        assert_eq!(eval("5 + (8 * 3 + 9 + 3 * 4 * 3)")?, 437);
+        assert_eq!(eval("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))")?, 12240);
         assert_eq!(
-            eval("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"),
+            eval("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2")?,
             13632
         );
         Ok(())
@@ -102,13 +259,13 @@ mod tests {
     #[test]
     fn test_eval2() -> Result<()> {
         use super::evaluate_naive2 as eval;
-        assert_eq!(eval("1 + 2 * 3 + 4 * 5 + 6"), 231);
-        assert_eq!(eval("1 + (2 * 3) + (4 * (5 + 6))"), 51);
-        assert_eq!(eval("2 * 3 + (4 * 5)"), 46);
-        assert_eq!(eval("5 + (8 * 3 + 9 + 3 * 4 * 3)"), 1445);
-        assert_eq!(eval("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))"), 669060);
+        assert_eq!(eval("1 + 2 * 3 + 4 * 5 + 6")?, 231);
+        assert_eq!(eval("1 + (2 * 3) + (4 * (5 + 6))")?, 51);
+        assert_eq!(eval("2 * 3 + (4 * 5)")?, 46);
+        assert_eq!(eval("5 + (8 * 3 + 9 + 3 * 4 * 3)")?, 1445);
+        assert_eq!(eval("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))")?, 669060);
         assert_eq!(
-            eval("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"),
+            eval("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2")?,
             23340
         );
         Ok(())
@@ -120,6 +277,10 @@ mod tests {
             super::challenge1(INPUT)?,
             71 + 51 + 26 + 437 + 12240 + 13632
         );
+        assert_eq!(
+            super::challenge1_from_reader(INPUT.as_bytes())?,
+            super::challenge1(INPUT)?
+        );
         Ok(())
     }
 
@@ -129,6 +290,26 @@ mod tests {
             super::challenge2(INPUT)?,
             231 + 51 + 46 + 1445 + 669060 + 23340
         );
+        assert_eq!(
+            super::challenge2_from_reader(INPUT.as_bytes())?,
+            super::challenge2(INPUT)?
+        );
         Ok(())
     }
+
+    #[test]
+    fn test_invalid_expression() {
+        let columns = |expr: &str| {
+            expr.parse::<super::Expression>()
+                .map(|_| ())
+                .map_err(|e| e.columns())
+        };
+        assert_eq!(columns("1 + (2 * 3)"), Ok(()));
+        assert_eq!(columns("1 + (2 * 3"), Err(5..6));
+        assert_eq!(columns("1 + 2) * 3"), Err(6..7));
+        assert_eq!(columns("1 + * 3"), Err(5..6));
+        assert_eq!(columns("12 34"), Err(4..5));
+        assert_eq!(columns("1 - 3"), Err(3..4));
+        assert_eq!(columns("1 +"), Err(4..5));
+    }
 }
@@ -1,30 +1,52 @@
-use std::{collections::HashMap, convert::TryFrom, vec};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+};
 
-use anyhow::Context as _;
 use anyhow::Result;
+use displaydoc::Display;
 use itertools::Itertools as _;
 use once_cell::sync::Lazy;
 use regex::Regex;
+use thiserror::Error;
+use utils::{Diagnostic, Solution};
 
+#[cfg(any(test, feature = "reference"))]
+pub mod reference;
+
+#[derive(Debug, Error, Display)]
+pub enum Errors {
+    /// Parse error {0}
+    ParseError(#[from] std::num::ParseIntError),
+    /// Expected a rule like "0: 4 1 5"
+    BadRule,
+    /// Expected the rules then the messages, separated by a blank line
+    MissingSection,
+    /// Rule {0} is referenced but never defined
+    MissingRule(u32),
+    /// A rule refers back to itself without matching anything in between
+    LeftRecursion,
+    /// Rules nest more than {0} references deep
+    TooDeep(usize),
+}
+
+/// Part of a rule alternative, either a literal to match or a reference to another rule
 #[derive(Debug, Clone)]
-enum RuleSegment<'s> {
+pub enum RuleSegment<'s> {
     Literal(&'s str),
     Reference(u32),
 }
 
-const LITERAL_RE: Lazy<Regex> =
+static LITERAL_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r#"^"([[:alpha:]])"$"#).expect("Bad regex"));
 
 impl<'s> TryFrom<&'s str> for RuleSegment<'s> {
-    type Error = anyhow::Error;
+    type Error = Errors;
 
     fn try_from(value: &'s str) -> Result<Self, Self::Error> {
         if let Some(captures) = LITERAL_RE.captures(value) {
             Ok(RuleSegment::Literal(
-                captures
-                    .get(1)
-                    .context("Bad regex, no capture group")?
-                    .as_str(),
+                captures.get(1).map_or("", |m| m.as_str()),
             ))
         } else {
             Ok(RuleSegment::Reference(value.parse()?))
@@ -32,76 +54,195 @@ impl<'s> TryFrom<&'s str> for RuleSegment<'s> {
     }
 }
 
-/// Return possible remaining strings after validating with the given rule.
-/// An empty remainder means that the rule matched the entire string
-fn valid_remainders<'s, 'r>(
-    message: &'s str,
-    rule: &Vec<RuleSegment<'s>>,
-    rules: &'r HashMap<u32, Vec<Vec<RuleSegment<'s>>>>,
-) -> Vec<&'s str> {
-    rule.iter()
-        .fold(vec![message], |remainders, segment| match segment {
-            RuleSegment::Literal(l) => remainders
-                .into_iter()
-                .filter_map(|remainder| {
-                    if remainder.len() >= l.len() && *l == &remainder[..l.len()] {
-                        Some(&remainder[l.len()..])
-                    } else {
-                        None
+/// Alternatives for each rule id, each alternative is a sequence of segments to match
+pub type Rules<'s> = HashMap<u32, Vec<Vec<RuleSegment<'s>>>>;
+
+/// Positions of the message a match of a rule starting at a position can end at
+type Ends = HashMap<(u32, usize), Vec<usize>>;
+
+/// Outcome of working out the ends of a rule from a start
+enum Step {
+    Done(Vec<usize>),
+    /// The ends of this rule from this start must be worked out first
+    Needs(u32, usize),
+}
+
+/// Whether rule 0 matches all of the message
+///
+/// The ends of each rule from each start are worked out once, with an explicit stack of the ones
+/// in progress rather than by recursion, so that deeply nested rules can't overflow the stack. A
+/// rule needing its own ends from a start it is still working out refers back to itself without
+/// consuming anything.
+fn matches(message: &str, rules: &Rules<'_>) -> Result<bool, Errors> {
+    let mut ends = Ends::new();
+    let mut stack = vec![(0, 0)];
+    let mut in_progress: HashSet<(u32, usize)> = stack.iter().copied().collect();
+
+    while let Some(&(id, start)) = stack.last() {
+        match rule_ends(message, rules, &ends, id, start)? {
+            Step::Done(found) => {
+                ends.insert((id, start), found);
+                in_progress.remove(&(id, start));
+                stack.pop();
+            }
+            Step::Needs(id, start) => {
+                if !in_progress.insert((id, start)) {
+                    return Err(Errors::LeftRecursion);
+                }
+                stack.push((id, start));
+            }
+        }
+    }
+
+    Ok(ends
+        .get(&(0, 0))
+        .is_some_and(|found| found.contains(&message.len())))
+}
+
+/// Ends of the matches of rule `id` from `start`, once the ends of the rules it refers to are known
+fn rule_ends(
+    message: &str,
+    rules: &Rules<'_>,
+    ends: &Ends,
+    id: u32,
+    start: usize,
+) -> Result<Step, Errors> {
+    let alternatives = rules.get(&id).ok_or(Errors::MissingRule(id))?;
+    let mut found = Vec::new();
+
+    for alternative in alternatives {
+        let mut positions = vec![start];
+        for segment in alternative {
+            let mut next = Vec::new();
+            for &position in &positions {
+                match segment {
+                    RuleSegment::Literal(l) => {
+                        if message[position..].starts_with(l) {
+                            next.push(position + l.len());
+                        }
                     }
-                })
-                .collect(),
-            RuleSegment::Reference(r) => rules
-                .get(r)
-                .into_iter()
-                .flat_map(|options| options.iter())
-                .flat_map(|rule| {
-                    remainders.iter().flat_map(move |remainder| {
-                        valid_remainders(remainder, rule, rules).into_iter()
-                    })
-                })
-                .collect(),
-        })
+                    RuleSegment::Reference(r) => match ends.get(&(*r, position)) {
+                        Some(reference_ends) => next.extend(reference_ends),
+                        None => return Ok(Step::Needs(*r, position)),
+                    },
+                }
+            }
+            next.sort_unstable();
+            next.dedup();
+            positions = next;
+        }
+        found.extend(positions);
+    }
+
+    found.sort_unstable();
+    found.dedup();
+    Ok(Step::Done(found))
 }
 
-pub fn challenge1(input: &str) -> Result<usize> {
-    let (rules, messages): (&str, &str) = input
-        .split("\n\n")
+/// Satellite messages along with the rules they must match
+pub struct Messages<'s> {
+    rules: Rules<'s>,
+    messages: Vec<&'s str>,
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Input<'i> = Messages<'i>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(count_matching(&input.rules, &input.messages)?)
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(count_matching(
+            &with_loops(input.rules.clone()),
+            &input.messages,
+        )?)
+    }
+}
+
+fn parse(input: &str) -> Result<Messages<'_>, Diagnostic> {
+    let (rules, messages) = utils::records(input)
         .collect_tuple()
-        .context("Input did not have two sections")?;
-
-    let rules: HashMap<u32, Vec<Vec<RuleSegment>>> = rules
-        .lines()
-        .map(|line| -> Result<(u32, Vec<Vec<RuleSegment>>)> {
-            let (id, rules): (&str, &str) = line
-                .split(": ")
-                .collect_tuple()
-                .context("Line did not have two sections")?;
-
-            let alternatives = rules
-                .split(" | ")
-                .map(|alternative| {
-                    alternative
-                        .split(" ")
-                        .map(RuleSegment::try_from)
-                        .try_collect()
-                        .context(format!("Parsing rule alternative {}", alternative))
-                })
-                .try_collect()
-                .context("Parsing rule")?;
+        .ok_or_else(|| Diagnostic::end_of_input(input, Errors::MissingSection))?;
 
-            Ok((id.parse().context("Parsing rule id")?, alternatives))
-        })
+    let rules: Rules = rules
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| parse_rule(line).map_err(|e| e.on_line(rules.line + index)))
         .try_collect()?;
 
-    Ok(messages
-        .lines()
-        .filter(|message| {
-            valid_remainders(message, &rules[&0][0], &rules)
-                .into_iter()
-                .any(|remainder| remainder == "")
+    Ok(Messages {
+        rules,
+        messages: messages.lines,
+    })
+}
+
+/// Rule id and its alternatives, like `1: 2 3 | 3 2`
+fn parse_rule(line: &str) -> Result<(u32, Vec<Vec<RuleSegment<'_>>>), Diagnostic> {
+    let (id, alternatives) = line
+        .split_once(": ")
+        .ok_or_else(|| Diagnostic::new(line, line, Errors::BadRule))?;
+
+    let alternatives = alternatives
+        .split(" | ")
+        .map(|alternative| {
+            alternative
+                .split(' ')
+                .map(|segment| {
+                    RuleSegment::try_from(segment).map_err(|e| Diagnostic::new(line, segment, e))
+                })
+                .collect()
         })
-        .count())
+        .try_collect()?;
+    let id = id
+        .parse()
+        .map_err(|e| Diagnostic::new(line, id, Errors::from(e)))?;
+
+    Ok((id, alternatives))
+}
+
+/// Replace rules 8 and 11 with their looping versions `8: 42 | 42 8` and `11: 42 31 | 42 11 31`
+fn with_loops(mut rules: Rules<'_>) -> Rules<'_> {
+    use RuleSegment::Reference;
+
+    rules.insert(
+        8,
+        vec![vec![Reference(42)], vec![Reference(42), Reference(8)]],
+    );
+    rules.insert(
+        11,
+        vec![
+            vec![Reference(42), Reference(31)],
+            vec![Reference(42), Reference(11), Reference(31)],
+        ],
+    );
+    rules
+}
+
+/// Number of messages matching rule 0 completely
+fn count_matching(rules: &Rules<'_>, messages: &[&str]) -> Result<usize, Errors> {
+    messages.iter().try_fold(0, |count, message| {
+        Ok(count + usize::from(matches(message, rules)?))
+    })
+}
+
+pub fn challenge1(input: &str) -> Result<usize> {
+    let Messages { rules, messages } = parse(input)?;
+    Ok(count_matching(&rules, &messages)?)
+}
+
+pub fn challenge2(input: &str) -> Result<usize> {
+    let Messages { rules, messages } = parse(input)?;
+    Ok(count_matching(&with_loops(rules), &messages)?)
 }
 
 #[cfg(test)]
@@ -120,9 +261,120 @@ abbbab
 aaabbb
 aaaabbb"#;
 
+    const INPUT2: &str = r#"42: 9 14 | 10 1
+9: 14 27 | 1 26
+10: 23 14 | 28 1
+1: "a"
+11: 42 31
+5: 1 14 | 15 1
+19: 14 1 | 14 14
+12: 24 14 | 19 1
+16: 15 1 | 14 14
+31: 14 17 | 1 13
+6: 14 14 | 1 14
+2: 1 24 | 14 4
+0: 8 11
+13: 14 3 | 1 12
+15: 1 | 14
+17: 14 2 | 1 7
+23: 25 1 | 22 14
+28: 16 1
+4: 1 1
+20: 14 14 | 1 15
+3: 5 14 | 16 1
+27: 1 6 | 14 18
+14: "b"
+21: 14 1 | 1 14
+25: 1 1 | 1 14
+22: 14 14
+8: 42
+26: 14 22 | 1 20
+18: 15 15
+7: 14 5 | 1 21
+24: 14 1
+
+abbbbbabbbaaaababbaabbbbabababbbabbbbbbabaaaa
+bbabbbbaabaabba
+babbbbaabbbbbabbbbbbaabaaabaaa
+aaabbbbbbaaaabaababaabababbabaaabbababababaaa
+bbbbbbbaaaabbbbaaabbabaaa
+bbbababbbbaaaaaaaabbababaaababaabab
+ababaaaaaabaaab
+ababaaaaabbbaba
+baabbaaaabbaaaababbaababb
+abbbbabbbbaaaababbbbbbaaaababb
+aaaaabbaabaaaaababaa
+aaaabbaaaabbaaa
+aaaabbaabbaaaaaaabbbabbbaaabbaabaaa
+babaaabbbaaabaababbaabababaaab
+aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba"#;
+
     #[test]
     fn test_challenge1() -> Result<()> {
         assert_eq!(super::challenge1(INPUT)?, 2);
+        assert_eq!(super::challenge1(INPUT2)?, 3);
         Ok(())
     }
+
+    #[test]
+    fn test_challenge2() -> Result<()> {
+        assert_eq!(super::challenge2(INPUT2)?, 12);
+        assert_eq!(super::challenge2(&INPUT2.replace('\n', "\r\n"))?, 12);
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_rules() -> Result<()> {
+        let error = super::challenge1("0: 0 1\n1: \"a\"\n\na")
+            .unwrap_err()
+            .downcast::<super::Errors>()?;
+        assert!(matches!(error, super::Errors::LeftRecursion));
+
+        let error = super::challenge1("1: \"a\"\n\na")
+            .unwrap_err()
+            .downcast::<super::Errors>()?;
+        assert!(matches!(error, super::Errors::MissingRule(0)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deep_rules() -> Result<()> {
+        // A chain of rules far deeper than the stack could recurse
+        let rules = 200_000;
+        let chain: String = (0..rules)
+            .map(|id| format!("{}: {}\n", id, id + 1))
+            .collect();
+        let input = format!("{}{}: \"a\"\n\na\nb\naa", chain, rules);
+        assert_eq!(super::challenge1(&input)?, 1);
+        let error = super::reference::challenge1(&input)
+            .unwrap_err()
+            .downcast::<super::Errors>()?;
+        assert!(matches!(error, super::Errors::TooDeep(_)));
+
+        let literals: String = (1..=3000).map(|id| format!("{}: \"a\"\n", id)).collect();
+        let input = format!("0: 0 1\n{}\n{}", literals, "a".repeat(200));
+        let error = super::challenge1(&input)
+            .unwrap_err()
+            .downcast::<super::Errors>()?;
+        assert!(matches!(error, super::Errors::LeftRecursion));
+        Ok(())
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_reference(input in generators::day19::messages()) {
+            let matching = super::challenge1(&input);
+            proptest::prop_assert!(matching.is_ok(), "Fast solver failed: {:?}", matching);
+            proptest::prop_assert_eq!(
+                matching.ok(),
+                super::reference::challenge1(&input).ok()
+            );
+            let matching = super::challenge2(&input);
+            proptest::prop_assert!(matching.is_ok(), "Fast solver failed: {:?}", matching);
+            proptest::prop_assert_eq!(
+                matching.ok(),
+                super::reference::challenge2(&input).ok()
+            );
+        }
+    }
 }
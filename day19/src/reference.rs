@@ -0,0 +1,88 @@
+//! Brute force solvers, slow but simple enough to check the fast ones against
+
+use anyhow::Result;
+
+use crate::{parse, with_loops, Errors, Messages, RuleSegment, Rules};
+
+/// [`challenge1`](crate::challenge1) trying every way to split each message between the
+/// segments of the rules
+pub fn challenge1(input: &str) -> Result<usize> {
+    let Messages { rules, messages } = parse(input)?;
+    Ok(count_matching(&rules, &messages)?)
+}
+
+/// [`challenge2`](crate::challenge2) trying every way to split each message between the
+/// segments of the rules
+pub fn challenge2(input: &str) -> Result<usize> {
+    let Messages { rules, messages } = parse(input)?;
+    Ok(count_matching(&with_loops(rules), &messages)?)
+}
+
+/// Deepest nesting of rule references followed, the recursion stays well within a thread's stack
+const MAX_DEPTH: usize = 256;
+
+/// Number of messages matching rule 0 completely
+///
+/// Every segment matches at least one character, so a match can't follow more references than
+/// the message has characters times the number of rules: any deeper recursion goes through a rule
+/// referring back to itself without consuming anything. Past [`MAX_DEPTH`] references this gives
+/// up instead.
+fn count_matching(rules: &Rules<'_>, messages: &[&str]) -> Result<usize, Errors> {
+    messages.iter().try_fold(0, |count, message| {
+        let bound = (message.len() + 1) * (rules.len() + 1);
+        let matched = match matches_rule(message, 0, rules, bound.min(MAX_DEPTH)) {
+            Err(Errors::LeftRecursion) if bound > MAX_DEPTH => Err(Errors::TooDeep(MAX_DEPTH)),
+            matched => matched,
+        }?;
+        Ok(count + usize::from(matched))
+    })
+}
+
+/// Whether one of the alternatives of rule `id` matches all of `message`
+fn matches_rule(message: &str, id: u32, rules: &Rules<'_>, depth: usize) -> Result<bool, Errors> {
+    let alternatives = rules.get(&id).ok_or(Errors::MissingRule(id))?;
+    let depth = depth.checked_sub(1).ok_or(Errors::LeftRecursion)?;
+
+    for alternative in alternatives {
+        if matches_sequence(message, alternative, rules, depth)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Whether `message` splits into non empty parts, each matching the segment at the same position
+fn matches_sequence(
+    message: &str,
+    segments: &[RuleSegment<'_>],
+    rules: &Rules<'_>,
+    depth: usize,
+) -> Result<bool, Errors> {
+    let (first, rest) = match segments {
+        [] => return Ok(message.is_empty()),
+        [last] => return matches_segment(message, last, rules, depth),
+        [first, rest @ ..] => (first, rest),
+    };
+
+    for split in (1..message.len()).filter(|&split| message.is_char_boundary(split)) {
+        let (head, tail) = message.split_at(split);
+        if matches_segment(head, first, rules, depth)?
+            && matches_sequence(tail, rest, rules, depth)?
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn matches_segment(
+    message: &str,
+    segment: &RuleSegment<'_>,
+    rules: &Rules<'_>,
+    depth: usize,
+) -> Result<bool, Errors> {
+    match segment {
+        RuleSegment::Literal(literal) => Ok(message == *literal),
+        RuleSegment::Reference(id) => matches_rule(message, *id, rules, depth),
+    }
+}
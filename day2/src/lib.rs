@@ -1,65 +1,157 @@
+use std::io::BufRead;
+
 use anyhow::Result;
+use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::Serialize;
+use utils::{Diagnostic, Solution};
 
-pub fn challenge1(input: &str) -> Result<usize> {
-    let pattern = Regex::new("^([[:digit:]]+)-([[:digit:]]+) ([[:alpha:]]): ([[:alpha:]]+)$")?;
-
-    let mut valid_count = 0;
-    for line in input.lines() {
-        let matches = pattern
-            .captures(line)
-            .ok_or_else(|| anyhow::anyhow!("Line didn't match the regex"))?;
-
-        let min: usize = matches[1].parse()?;
-        let max: usize = matches[2].parse()?;
-        let expected = &matches[3];
-        let password = &matches[4];
-
-        if password_valid_policy1(min, max, expected, password) {
-            valid_count += 1;
-        }
-    }
+static ENTRY_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new("^([0-9]+)-([0-9]+) ([^[:space:]]+?): (.*)$").expect("Bad regex"));
+
+mod policy;
+mod report;
 
-    Ok(valid_count)
+pub use policy::{
+    Bound, CharClass, ForbiddenSubstrings, MinEntropy, MinLength, PasswordPolicy, PolicyCount,
+    PolicyEngine, PositionError, RequiredClasses, SledRentalPolicy, TobogganPolicy, Violation,
+};
+pub use report::{report, LineReport, Verdict};
+
+/// A line of the password database, two numbers whose meaning depends on the policy, a target and a
+/// password
+///
+/// The target is one or more characters without spaces, the password is whatever follows the
+/// `": "` up to the end of the line, spaces included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PasswordEntry<'i> {
+    pub a: usize,
+    pub b: usize,
+    pub target: &'i str,
+    pub password: &'i str,
+    #[serde(skip)]
+    spans: Spans<'i>,
 }
 
-fn password_valid_policy1(min: usize, max: usize, expected: &str, password: &str) -> bool {
-    let occurrences = password.split(expected).count() - 1;
-    min <= occurrences && occurrences <= max
+/// Where an entry was parsed from, so that the errors checking it can point at the line and number
+/// at fault
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Spans<'i> {
+    /// 1-based line number
+    number: usize,
+    line: &'i str,
+    a: &'i str,
+    b: &'i str,
 }
 
-pub fn challenge2(input: &str) -> Result<usize> {
-    let pattern = Regex::new("^([[:digit:]]+)-([[:digit:]]+) ([[:alpha:]]): ([[:alpha:]]+)$")?;
-
-    let mut valid_count = 0;
-    for line in input.lines() {
-        let matches = pattern
-            .captures(line)
-            .ok_or_else(|| anyhow::anyhow!("Line didn't match the regex"))?;
-
-        let a: usize = matches[1].parse()?;
-        let b: usize = matches[2].parse()?;
-        let expected = &matches[3];
-        let haystack = &matches[4];
-
-        if password_valid_policy2(a, b, expected, haystack)? {
-            valid_count += 1;
-        }
+impl PasswordEntry<'_> {
+    /// Point an error checking the entry at the number it is about, or at the whole line when the
+    /// error does not say
+    fn check_diagnostic(&self, error: anyhow::Error) -> Diagnostic {
+        let Spans { number, line, a, b } = self.spans;
+        let token = match error
+            .downcast_ref::<PositionError>()
+            .map(PositionError::bound)
+        {
+            Some(Bound::A) => a,
+            Some(Bound::B) => b,
+            None => line,
+        };
+
+        Diagnostic::new(line, token, error).on_line(number)
+    }
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input<'i> = Vec<PasswordEntry<'i>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(count_valid(SledRentalPolicy, input)?)
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(count_valid(TobogganPolicy, input)?)
     }
+}
+
+fn parse(input: &str) -> Result<Vec<PasswordEntry<'_>>, Diagnostic> {
+    input
+        .lines()
+        .zip(1..)
+        .map(|(line, number)| parse_entry(line, number))
+        .collect()
+}
+
+/// Entry on the line `number`
+fn parse_entry(line: &str, number: usize) -> Result<PasswordEntry<'_>, Diagnostic> {
+    let matches = ENTRY_RE.captures(line).ok_or_else(|| {
+        Diagnostic::new(line, line, "Expected an entry like \"1-3 a: abcde\"").on_line(number)
+    })?;
+    let token = |index| matches.get(index).map_or("", |m| m.as_str());
+    let number_at = |index| {
+        token(index)
+            .parse()
+            .map_err(|e| Diagnostic::new(line, token(index), e).on_line(number))
+    };
+
+    Ok(PasswordEntry {
+        a: number_at(1)?,
+        b: number_at(2)?,
+        target: token(3),
+        password: token(4),
+        spans: Spans {
+            number,
+            line,
+            a: token(1),
+            b: token(2),
+        },
+    })
+}
+
+/// Number of entries complying with a single policy
+fn count_valid(
+    policy: impl PasswordPolicy + 'static,
+    entries: &[PasswordEntry<'_>],
+) -> Result<usize> {
+    let mut engine = PolicyEngine::new();
+    engine.register(policy);
+    Ok(engine.evaluate(entries)?[0].valid)
+}
+
+/// Number of entries complying with a single policy, reading one entry at a time
+fn count_valid_from_reader(
+    policy: impl PasswordPolicy + 'static,
+    reader: impl BufRead,
+) -> Result<usize> {
+    let mut engine = PolicyEngine::new();
+    engine.register(policy);
+    Ok(engine.evaluate_reader(reader)?[0].valid)
+}
 
-    Ok(valid_count)
+pub fn challenge1(input: &str) -> Result<usize> {
+    count_valid(SledRentalPolicy, &parse(input)?)
 }
 
-fn password_valid_policy2(ia: usize, ib: usize, expected: &str, password: &str) -> Result<bool> {
-    let expected = expected
-        .chars()
-        .next()
-        .ok_or_else(|| anyhow::anyhow!("Empty condition"))?;
+pub fn challenge2(input: &str) -> Result<usize> {
+    count_valid(TobogganPolicy, &parse(input)?)
+}
 
-    let a = password.chars().nth(ia - 1);
-    let b = password.chars().nth(ib - 1);
+/// [`challenge1`] reading one entry at a time
+pub fn challenge1_from_reader(reader: impl BufRead) -> Result<usize> {
+    count_valid_from_reader(SledRentalPolicy, reader)
+}
 
-    Ok((a == Some(expected)) ^ (b == Some(expected)))
+/// [`challenge2`] reading one entry at a time
+pub fn challenge2_from_reader(reader: impl BufRead) -> Result<usize> {
+    count_valid_from_reader(TobogganPolicy, reader)
 }
 
 #[cfg(test)]
@@ -73,6 +165,8 @@ mod tests {
     fn challenge1() -> Result<()> {
         let count = super::challenge1(INPUT)?;
         ensure!(count == 2, "Wrong number of valid reported ({})", count);
+        let count = super::challenge1_from_reader(INPUT.as_bytes())?;
+        ensure!(count == 2, "Wrong number of valid streamed ({})", count);
 
         Ok(())
     }
@@ -81,7 +175,172 @@ mod tests {
     fn challenge2() -> Result<()> {
         let count = super::challenge2(INPUT)?;
         ensure!(count == 1, "Wrong number of valid reported ({})", count);
+        let count = super::challenge2_from_reader(INPUT.as_bytes())?;
+        ensure!(count == 1, "Wrong number of valid streamed ({})", count);
+
+        Ok(())
+    }
+
+    #[test]
+    fn custom_policies() -> Result<()> {
+        use super::{
+            CharClass, ForbiddenSubstrings, MinEntropy, MinLength, PolicyCount, PolicyEngine,
+            RequiredClasses, SledRentalPolicy,
+        };
+
+        let database = "1-3 a: abcde\n1-3 b: passwordbb\n2-9 c: ccccccccc\n1-2 q: qwErtY";
+        let mut engine = PolicyEngine::new();
+        engine
+            .register(SledRentalPolicy)
+            .register(MinLength(6))
+            .register(RequiredClasses(vec![
+                CharClass::Lowercase,
+                CharClass::Uppercase,
+            ]))
+            .register(ForbiddenSubstrings(vec![
+                "password".to_string(),
+                "ccc".to_string(),
+            ]))
+            .register(MinEntropy(10.0));
+
+        let counts = engine.evaluate_reader(database.as_bytes())?;
+        let valid: Vec<usize> = counts.iter().map(|count| count.valid).collect();
+        ensure!(valid == vec![4, 3, 1, 2, 3], "Wrong counts {:?}", valid);
+        ensure!(
+            counts[1]
+                == PolicyCount {
+                    policy: "at least 6 graphemes".to_string(),
+                    valid: 3
+                },
+            "Wrong count {:?}",
+            counts[1]
+        );
+
+        ensure!(MinEntropy::bits("aaaa") == 0.0);
+        ensure!((MinEntropy::bits("abcd") - 8.0).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn violations() {
+        use super::{TobogganPolicy, Verdict, Violation};
+
+        let reports = super::report(INPUT, &TobogganPolicy);
+        let verdicts: Vec<&Verdict> = reports.iter().map(|report| &report.verdict).collect();
+        assert_eq!(
+            verdicts,
+            vec![
+                &Verdict::Valid,
+                &Verdict::Invalid {
+                    reason: Violation::NeitherPosition {
+                        target: "b".to_string(),
+                        a: 1,
+                        b: 3
+                    }
+                },
+                &Verdict::Invalid {
+                    reason: Violation::BothPositions {
+                        target: "c".to_string(),
+                        a: 2,
+                        b: 9
+                    }
+                },
+            ]
+        );
+        assert_eq!(reports[1].entry.map(|entry| entry.password), Some("cdefg"));
 
+        let reports = super::report(
+            "1-3 a: abcde\n0-3 a: abc\nx-3 a: abc\n1-14 a: abc",
+            &TobogganPolicy,
+        );
+        assert_eq!(reports.len(), 4);
+        assert_eq!(
+            reports[1].verdict,
+            Verdict::Unchecked {
+                column: 1,
+                error: "Positions start at 1".to_string()
+            }
+        );
+        assert_eq!(reports[1].entry.map(|entry| entry.password), Some("abc"));
+        assert!(reports[2].entry.is_none());
+        assert!(matches!(
+            reports[2].verdict,
+            Verdict::Malformed { column: 1, .. }
+        ));
+        assert_eq!(
+            reports[3].verdict,
+            Verdict::Unchecked {
+                column: 3,
+                error: "Position 14 is past the end of the password, which has 3 graphemes"
+                    .to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn unicode_targets() -> Result<()> {
+        use super::{MinLength, PasswordPolicy, SledRentalPolicy, TobogganPolicy};
+
+        let entries = super::parse(
+            "2-3 aa: aaa\n1-2 e: cafe\u{301}\n2-3 👍🏽: a👍🏽b👍🏽\n1-1 #1: p#1ss w0rd!\n1-2 é: été",
+        )?;
+        let sled: Vec<bool> = entries
+            .iter()
+            .map(|entry| SledRentalPolicy.is_valid(entry))
+            .collect::<Result<_>>()?;
+        ensure!(
+            sled == vec![true, false, true, true, true],
+            "Wrong sled rental verdicts {:?}",
+            sled
+        );
+        let toboggan: Vec<bool> = entries
+            .iter()
+            .map(|entry| TobogganPolicy.is_valid(entry))
+            .collect::<Result<_>>()?;
+        ensure!(
+            toboggan == vec![true, false, true, false, true],
+            "Wrong toboggan verdicts {:?}",
+            toboggan
+        );
+        ensure!(entries[3].password == "p#1ss w0rd!");
+
+        // `cafe\u{301}` is 5 characters but 4 graphemes
+        let short = MinLength(5)
+            .check(&entries[1])?
+            .map(|violation| violation.to_string());
+        ensure!(
+            short.as_deref() == Some("4 graphemes, at least 5 required"),
+            "Wrong length violation {:?}",
+            short
+        );
+        ensure!(MinLength(4).is_valid(&entries[1])?);
+
+        let error = super::challenge2("1-3 a: abc\n1-9 a: abcé").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Line 2, column 3: Position 9 is past the end of the password, which has 4 graphemes"
+        );
+        let error =
+            super::challenge2_from_reader("1-3 a: abc\n1-9 a: abcé".as_bytes()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid input: Line 2, column 3: Position 9 is past the end of the password, which has 4 graphemes"
+        );
         Ok(())
     }
+
+    #[test]
+    fn malformed_entry() {
+        let error = super::challenge1("1-3 a: abcde\n1-3 b cdefg").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Line 2, column 1: Expected an entry like \"1-3 a: abcde\""
+        );
+        let error =
+            super::challenge2_from_reader("1-3 a: abcde\n0-3 b: cdefg".as_bytes()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid input: Line 2, column 1: Positions start at 1"
+        );
+    }
 }
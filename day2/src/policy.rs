@@ -0,0 +1,382 @@
+use anyhow::Result;
+use displaydoc::Display;
+use serde::Serialize;
+use std::{collections::HashMap, io::BufRead, iter};
+use thiserror::Error;
+use unicode_segmentation::UnicodeSegmentation;
+use utils::Diagnostic;
+
+use crate::PasswordEntry;
+
+/// Rule a password of the database must follow
+pub trait PasswordPolicy {
+    /// Name of the policy in the counts
+    fn name(&self) -> String;
+
+    /// Why the password of the entry does not comply, `None` if it does, fails if the entry can
+    /// not be checked against the policy at all
+    fn check(&self, entry: &PasswordEntry<'_>) -> Result<Option<Violation>>;
+
+    /// Whether the password of the entry complies
+    fn is_valid(&self, entry: &PasswordEntry<'_>) -> Result<bool> {
+        Ok(self.check(entry)?.is_none())
+    }
+}
+
+/// Why a password does not comply with a policy
+#[derive(Debug, Clone, PartialEq, Display, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Violation {
+    /// {target:?} appears {count} times, allowed {min}–{max}
+    Occurrences {
+        target: String,
+        count: usize,
+        min: usize,
+        max: usize,
+    },
+    /// both positions {a} and {b} contain {target:?}
+    BothPositions { target: String, a: usize, b: usize },
+    /// neither position {a} nor {b} contains {target:?}
+    NeitherPosition { target: String, a: usize, b: usize },
+    /// {length} graphemes, at least {min} required
+    TooShort { length: usize, min: usize },
+    /// no {class}
+    MissingClass { class: CharClass },
+    /// contains the forbidden {substring:?}
+    Forbidden { substring: String },
+    /// {bits:.1} bits of entropy, at least {min} required
+    LowEntropy { bits: f64, min: f64 },
+    /// {message}
+    Custom { message: String },
+}
+
+/// Byte offsets the graphemes of the password start at, followed by its length
+fn grapheme_boundaries(password: &str) -> Vec<usize> {
+    password
+        .grapheme_indices(true)
+        .map(|(index, _)| index)
+        .chain(iter::once(password.len()))
+        .collect()
+}
+
+/// Whether the target is in the password from the byte `start` to a grapheme boundary, so that an
+/// `e` is not found in an `é` written with a combining accent
+fn matches_at(password: &str, boundaries: &[usize], start: usize, target: &str) -> bool {
+    password[start..].starts_with(target)
+        && boundaries.binary_search(&(start + target.len())).is_ok()
+}
+
+/// Policy of the sled rental place: the target appears between `a` and `b` times in the password
+///
+/// Overlapping occurrences all count, `aa` appears twice in `aaa`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SledRentalPolicy;
+
+impl PasswordPolicy for SledRentalPolicy {
+    fn name(&self) -> String {
+        "sled rental".to_string()
+    }
+
+    fn check(&self, entry: &PasswordEntry<'_>) -> Result<Option<Violation>> {
+        let boundaries = grapheme_boundaries(entry.password);
+        let count = boundaries[..boundaries.len() - 1]
+            .iter()
+            .filter(|&&start| matches_at(entry.password, &boundaries, start, entry.target))
+            .count();
+        let violation = Violation::Occurrences {
+            target: entry.target.to_string(),
+            count,
+            min: entry.a,
+            max: entry.b,
+        };
+        Ok((count < entry.a || count > entry.b).then_some(violation))
+    }
+}
+
+/// One of the two numbers of an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    A,
+    B,
+}
+
+/// A number of the entry is not a position in its password
+#[derive(Debug, Error, Display)]
+pub enum PositionError {
+    /// Positions start at 1
+    Zero { bound: Bound },
+    /// Position {position} is past the end of the password, which has {graphemes} graphemes
+    PastEnd {
+        bound: Bound,
+        position: usize,
+        graphemes: usize,
+    },
+}
+
+impl PositionError {
+    /// The number of the entry at fault
+    pub fn bound(&self) -> Bound {
+        match *self {
+            PositionError::Zero { bound } | PositionError::PastEnd { bound, .. } => bound,
+        }
+    }
+}
+
+/// Official Toboggan Corporate Policy: the target starts at exactly one of the positions `a` and
+/// `b` of the password
+///
+/// Positions count graphemes from 1, fails with a [`PositionError`] when a position is 0 or past
+/// the last grapheme.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TobogganPolicy;
+
+impl PasswordPolicy for TobogganPolicy {
+    fn name(&self) -> String {
+        "toboggan".to_string()
+    }
+
+    fn check(&self, entry: &PasswordEntry<'_>) -> Result<Option<Violation>> {
+        let boundaries = grapheme_boundaries(entry.password);
+        let graphemes = boundaries.len() - 1;
+        let contains = |bound: Bound, position: usize| -> Result<bool, PositionError> {
+            if position == 0 {
+                return Err(PositionError::Zero { bound });
+            }
+            if position > graphemes {
+                return Err(PositionError::PastEnd {
+                    bound,
+                    position,
+                    graphemes,
+                });
+            }
+            let start = boundaries[position - 1];
+            Ok(matches_at(entry.password, &boundaries, start, entry.target))
+        };
+        let (target, a, b) = (entry.target.to_string(), entry.a, entry.b);
+
+        Ok(match (contains(Bound::A, a)?, contains(Bound::B, b)?) {
+            (true, true) => Some(Violation::BothPositions { target, a, b }),
+            (false, false) => Some(Violation::NeitherPosition { target, a, b }),
+            _ => None,
+        })
+    }
+}
+
+/// The password has at least this many graphemes, so that an `é` written with a combining accent
+/// counts once
+#[derive(Debug, Clone, Copy)]
+pub struct MinLength(pub usize);
+
+impl PasswordPolicy for MinLength {
+    fn name(&self) -> String {
+        format!("at least {} graphemes", self.0)
+    }
+
+    fn check(&self, entry: &PasswordEntry<'_>) -> Result<Option<Violation>> {
+        let length = entry.password.graphemes(true).count();
+        Ok((length < self.0).then_some(Violation::TooShort {
+            length,
+            min: self.0,
+        }))
+    }
+}
+
+/// Kind of character a password can be required to contain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CharClass {
+    /// lowercase letter
+    Lowercase,
+    /// uppercase letter
+    Uppercase,
+    /// digit
+    Digit,
+    /// symbol, anything that is neither a letter nor a digit
+    Symbol,
+}
+
+impl CharClass {
+    pub fn contains(self, c: char) -> bool {
+        match self {
+            CharClass::Lowercase => c.is_lowercase(),
+            CharClass::Uppercase => c.is_uppercase(),
+            CharClass::Digit => c.is_numeric(),
+            CharClass::Symbol => !c.is_alphanumeric(),
+        }
+    }
+}
+
+/// The password has a character of each of the classes
+#[derive(Debug, Clone)]
+pub struct RequiredClasses(pub Vec<CharClass>);
+
+impl PasswordPolicy for RequiredClasses {
+    fn name(&self) -> String {
+        format!("contains {:?}", self.0)
+    }
+
+    fn check(&self, entry: &PasswordEntry<'_>) -> Result<Option<Violation>> {
+        Ok(self
+            .0
+            .iter()
+            .find(|&&class| !entry.password.chars().any(|c| class.contains(c)))
+            .map(|&class| Violation::MissingClass { class }))
+    }
+}
+
+/// The password contains none of the substrings, letter case included
+#[derive(Debug, Clone)]
+pub struct ForbiddenSubstrings(pub Vec<String>);
+
+impl PasswordPolicy for ForbiddenSubstrings {
+    fn name(&self) -> String {
+        format!("without {:?}", self.0)
+    }
+
+    fn check(&self, entry: &PasswordEntry<'_>) -> Result<Option<Violation>> {
+        Ok(self
+            .0
+            .iter()
+            .find(|forbidden| entry.password.contains(forbidden.as_str()))
+            .map(|substring| Violation::Forbidden {
+                substring: substring.clone(),
+            }))
+    }
+}
+
+/// The password carries at least this many bits of entropy
+///
+/// The entropy is estimated from the password alone, as its length times the Shannon entropy of
+/// the frequencies of its characters: `aaaa` has none and `abcd` has 8 bits.
+#[derive(Debug, Clone, Copy)]
+pub struct MinEntropy(pub f64);
+
+impl MinEntropy {
+    /// Estimated entropy of a password in bits
+    pub fn bits(password: &str) -> f64 {
+        let mut frequencies = HashMap::new();
+        for c in password.chars() {
+            *frequencies.entry(c).or_insert(0_usize) += 1;
+        }
+
+        let length = frequencies.values().sum::<usize>() as f64;
+        frequencies
+            .values()
+            .map(|&count| {
+                let probability = count as f64 / length;
+                -(count as f64) * probability.log2()
+            })
+            .sum()
+    }
+}
+
+impl PasswordPolicy for MinEntropy {
+    fn name(&self) -> String {
+        format!("at least {} bits of entropy", self.0)
+    }
+
+    fn check(&self, entry: &PasswordEntry<'_>) -> Result<Option<Violation>> {
+        let bits = Self::bits(entry.password);
+        Ok((bits < self.0).then_some(Violation::LowEntropy { bits, min: self.0 }))
+    }
+}
+
+/// Number of entries of a database complying with a policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyCount {
+    pub policy: String,
+    pub valid: usize,
+}
+
+/// Set of policies a whole database is checked against at once
+///
+/// # Examples
+///
+/// ```
+/// use day2::{Day2, MinLength, PolicyEngine, SledRentalPolicy, TobogganPolicy};
+/// use utils::Solution as _;
+///
+/// let entries = Day2.parse("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc").unwrap();
+/// let mut engine = PolicyEngine::new();
+/// engine
+///     .register(SledRentalPolicy)
+///     .register(TobogganPolicy)
+///     .register(MinLength(6));
+///
+/// let valid: Vec<usize> = engine
+///     .evaluate(&entries)
+///     .unwrap()
+///     .iter()
+///     .map(|count| count.valid)
+///     .collect();
+/// assert_eq!(valid, vec![2, 1, 1]);
+/// ```
+#[derive(Default)]
+pub struct PolicyEngine {
+    policies: Vec<Box<dyn PasswordPolicy>>,
+}
+
+impl PolicyEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a policy, the counts come in the order the policies were registered in
+    pub fn register(&mut self, policy: impl PasswordPolicy + 'static) -> &mut Self {
+        self.policies.push(Box::new(policy));
+        self
+    }
+
+    /// Count the entries complying with each policy, going over the entries once, the errors
+    /// point at the entry
+    pub fn evaluate<'e, I>(&self, entries: I) -> Result<Vec<PolicyCount>>
+    where
+        I: IntoIterator<Item = &'e PasswordEntry<'e>>,
+    {
+        let mut valid = vec![0; self.policies.len()];
+        for entry in entries {
+            self.tally(entry, &mut valid)?;
+        }
+
+        Ok(self.counts(valid))
+    }
+
+    /// [`PolicyEngine::evaluate`] reading one entry at a time
+    pub fn evaluate_reader(&self, reader: impl BufRead) -> Result<Vec<PolicyCount>> {
+        let mut valid = vec![0; self.policies.len()];
+        let mut number = 0;
+        for entry in utils::read_lines(reader, |line| {
+            number += 1;
+            self.tally(&crate::parse_entry(line, number)?, &mut valid)
+        }) {
+            entry?;
+        }
+
+        Ok(self.counts(valid))
+    }
+
+    /// Add the policies the entry complies with to the counts
+    fn tally(&self, entry: &PasswordEntry<'_>, valid: &mut [usize]) -> Result<(), Diagnostic> {
+        for (policy, count) in self.policies.iter().zip(valid.iter_mut()) {
+            if policy
+                .is_valid(entry)
+                .map_err(|e| entry.check_diagnostic(e))?
+            {
+                *count += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn counts(&self, valid: Vec<usize>) -> Vec<PolicyCount> {
+        self.policies
+            .iter()
+            .zip(valid)
+            .map(|(policy, valid)| PolicyCount {
+                policy: policy.name(),
+                valid,
+            })
+            .collect()
+    }
+}
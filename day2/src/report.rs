@@ -0,0 +1,98 @@
+use serde::Serialize;
+
+use crate::{PasswordEntry, PasswordPolicy, Violation};
+
+/// Outcome of checking one line of the database
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "verdict", rename_all = "snake_case")]
+pub enum Verdict {
+    Valid,
+    Invalid {
+        reason: Violation,
+    },
+    /// The line is not an entry
+    Malformed {
+        column: usize,
+        error: String,
+    },
+    /// The line is an entry the policy can not be checked against
+    Unchecked {
+        column: usize,
+        error: String,
+    },
+}
+
+/// A line of the database along with its verdict
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineReport<'i> {
+    /// 1-based line number
+    pub line: usize,
+    /// The entry as parsed, absent when the line is malformed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry: Option<PasswordEntry<'i>>,
+    #[serde(flatten)]
+    pub verdict: Verdict,
+}
+
+/// Check every line of the database against the policy, the malformed lines and the entries the
+/// policy can not check are reported along with the others instead of stopping the check
+///
+/// # Examples
+///
+/// ```
+/// use day2::{SledRentalPolicy, Verdict};
+///
+/// let reports = day2::report("1-3 a: abcde\n1-3 b: cdefg\n1-3 c", &SledRentalPolicy);
+/// assert_eq!(reports[0].verdict, Verdict::Valid);
+/// match &reports[1].verdict {
+///     Verdict::Invalid { reason } => {
+///         assert_eq!(reason.to_string(), "\"b\" appears 0 times, allowed 1–3")
+///     }
+///     verdict => panic!("Unexpected verdict {:?}", verdict),
+/// }
+/// assert!(matches!(reports[2].verdict, Verdict::Malformed { column: 1, .. }));
+///
+/// // The entry parsed, the position 12 is past the end of its password
+/// let reports = day2::report("2-12 a: abcde", &day2::TobogganPolicy);
+/// assert_eq!(reports[0].entry.map(|entry| entry.b), Some(12));
+/// assert!(matches!(reports[0].verdict, Verdict::Unchecked { column: 3, .. }));
+/// ```
+pub fn report<'i>(input: &'i str, policy: &dyn PasswordPolicy) -> Vec<LineReport<'i>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let (entry, verdict) = match crate::parse_entry(line, index + 1) {
+                Ok(entry) => (Some(entry), verdict(&entry, policy)),
+                Err(diagnostic) => (
+                    None,
+                    Verdict::Malformed {
+                        column: diagnostic.columns().start,
+                        error: diagnostic.cause().to_string(),
+                    },
+                ),
+            };
+
+            LineReport {
+                line: index + 1,
+                entry,
+                verdict,
+            }
+        })
+        .collect()
+}
+
+/// Verdict of the policy on the entry
+fn verdict(entry: &PasswordEntry<'_>, policy: &dyn PasswordPolicy) -> Verdict {
+    match policy.check(entry) {
+        Ok(None) => Verdict::Valid,
+        Ok(Some(reason)) => Verdict::Invalid { reason },
+        Err(e) => {
+            let diagnostic = entry.check_diagnostic(e);
+            Verdict::Unchecked {
+                column: diagnostic.columns().start,
+                error: diagnostic.cause().to_string(),
+            }
+        }
+    }
+}
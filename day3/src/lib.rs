@@ -1,11 +1,37 @@
+use displaydoc::Display;
+use std::convert::TryFrom;
+use thiserror::Error;
+use utils::{Diagnostic, Solution};
+
+mod map;
+mod trace;
+
+pub use map::TreeMap;
+pub use trace::{Stop, Trace, Visit, Walk};
+
+#[derive(Debug, Error, Display)]
+pub enum Errors {
+    /// Invalid map: {0}
+    InvalidMap(#[from] Diagnostic),
+    /// Slope must move up or down to reach the edge of the map
+    FlatSlope,
+    /// Answer does not fit in a usize
+    Overflow,
+}
+
+/// Invalid character {0:?}, expected '.' or '#'
+#[derive(Debug, Error, Display)]
+pub struct InvalidLocation(char);
+
+/// Move from a square to the next, right and down are positive
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Slope {
-    pub horizontal: usize,
-    pub vertical: usize,
+    pub horizontal: isize,
+    pub vertical: isize,
 }
 
 impl Slope {
-    pub fn new(horizontal: usize, vertical: usize) -> Self {
+    pub fn new(horizontal: isize, vertical: isize) -> Self {
         Self {
             horizontal,
             vertical,
@@ -13,53 +39,75 @@ impl Slope {
     }
 }
 
+/// A square of the map
 #[derive(Copy, Clone, Debug, PartialEq)]
-enum Location {
+pub enum Location {
     Tree,
     Open,
 }
 
-impl From<char> for Location {
-    fn from(c: char) -> Self {
+impl TryFrom<char> for Location {
+    type Error = InvalidLocation;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
         match c {
-            '#' => Self::Tree,
-            _ => Self::Open,
+            '#' => Ok(Self::Tree),
+            '.' => Ok(Self::Open),
+            _ => Err(InvalidLocation(c)),
         }
     }
 }
 
-pub fn challenge1(input: &str) -> usize {
-    count_trees(input, &Slope::new(3, 1))
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input<'i> = TreeMap;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(input.parse::<TreeMap>()?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(count_trees(input, &Slope::new(3, 1))?)
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(slopes_product(input)?)
+    }
 }
 
-pub fn challenge2(input: &str) -> usize {
-    [
+pub fn challenge1(input: &str) -> Result<usize, Errors> {
+    count_trees(&input.parse()?, &Slope::new(3, 1))
+}
+
+pub fn challenge2(input: &str) -> Result<usize, Errors> {
+    slopes_product(&input.parse()?)
+}
+
+/// Product of the trees encountered on each of the slopes to check
+fn slopes_product(map: &TreeMap) -> Result<usize, Errors> {
+    map.count_trees(&[
         Slope::new(1, 1),
         Slope::new(3, 1),
         Slope::new(5, 1),
         Slope::new(7, 1),
         Slope::new(1, 2),
-    ]
-    .iter()
-    .map(|slope| count_trees(input, slope))
-    .product()
+    ])?
+    .into_iter()
+    .try_fold(1_usize, |product, trees| {
+        product.checked_mul(trees).ok_or(Errors::Overflow)
+    })
 }
 
-pub fn count_trees(input: &str, slope: &Slope) -> usize {
-    input
-        .lines()
-        .step_by(slope.vertical)
-        .enumerate()
-        .filter_map(|(index, line)| line.chars().cycle().step_by(slope.horizontal).nth(index))
-        .map(Location::from)
-        .filter(|&location| location == Location::Tree)
-        .count()
+/// Count the trees encountered going down the map, the map repeats to the right and left
+pub fn count_trees(map: &TreeMap, slope: &Slope) -> Result<usize, Errors> {
+    Ok(map.count_trees(&[*slope])?[0])
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::Slope;
-
     const INPUT: &str = r#"..##.......
 #...#...#..
 .#....#..#.
@@ -73,22 +121,156 @@ mod tests {
 .#..#...#.#"#;
 
     #[test]
-    fn challenge1() {
-        assert_eq!(super::challenge1(INPUT), 7);
+    fn challenge1() -> Result<(), super::Errors> {
+        assert_eq!(super::challenge1(INPUT)?, 7);
+        Ok(())
     }
     #[test]
-    fn challenge2() {
-        assert_eq!(super::challenge2(INPUT), 336);
+    fn challenge2() -> Result<(), super::Errors> {
+        assert_eq!(super::challenge2(INPUT)?, 336);
+        Ok(())
     }
 
     #[test]
-    fn count_trees() {
+    fn count_trees() -> Result<(), super::Errors> {
         use super::{count_trees, Slope};
+        let map = INPUT.parse()?;
 
-        assert_eq!(count_trees(INPUT, &Slope::new(1, 1)), 2);
-        assert_eq!(count_trees(INPUT, &Slope::new(3, 1)), 7);
-        assert_eq!(count_trees(INPUT, &Slope::new(5, 1)), 3);
-        assert_eq!(count_trees(INPUT, &Slope::new(7, 1)), 4);
-        assert_eq!(count_trees(INPUT, &Slope::new(1, 2)), 2);
+        assert_eq!(count_trees(&map, &Slope::new(1, 1))?, 2);
+        assert_eq!(count_trees(&map, &Slope::new(3, 1))?, 7);
+        assert_eq!(count_trees(&map, &Slope::new(5, 1))?, 3);
+        assert_eq!(count_trees(&map, &Slope::new(7, 1))?, 4);
+        assert_eq!(count_trees(&map, &Slope::new(1, 2))?, 2);
+        assert!(matches!(
+            count_trees(&map, &Slope::new(1, 0)),
+            Err(super::Errors::FlatSlope)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn tree_map() -> Result<(), super::Errors> {
+        use super::{Location, Slope, TreeMap};
+        use utils::Grid;
+
+        let grid: Grid<Location> = INPUT.parse()?;
+        let map = TreeMap::from(&grid);
+        assert_eq!((map.width(), map.height()), (11, 11));
+        for y in 0..grid.height() {
+            for x in 0..grid.width() * 3 {
+                let tree = grid.get(y, x % grid.width()) == Some(&Location::Tree);
+                assert_eq!(map.is_tree(x, y), tree, "Wrong square at {}, {}", x, y);
+            }
+        }
+        assert!(!map.is_tree(0, 11));
+
+        // Rows wider than a word
+        let wide: TreeMap = format!("{}#\n#{}", ".".repeat(99), ".".repeat(99)).parse()?;
+        assert!(wide.is_tree(99, 0) && wide.is_tree(199, 0) && !wide.is_tree(98, 0));
+        assert!(wide.is_tree(100, 1) && !wide.is_tree(64, 1));
+
+        // isize::MAX is 7 more than a multiple of the width
+        let steep = [
+            Slope::new(isize::MAX, 1),
+            Slope::new(3, 20),
+            Slope::new(0, 1),
+            Slope::new(-4, 1),
+            Slope::new(5, -1),
+        ];
+        let trees = map.count_trees(&steep)?;
+        assert_eq!(
+            trees,
+            vec![
+                super::count_trees(&map, &Slope::new(7, 1))?,
+                0,
+                3,
+                super::count_trees(&map, &Slope::new(7, 1))?,
+                0
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn trace() -> Result<(), super::Errors> {
+        use super::{Errors, Location, Slope, Stop, TreeMap, Visit, Walk};
+
+        let map: TreeMap = INPUT.parse()?;
+        for &(horizontal, vertical) in &[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2), (-2, 3)] {
+            let slope = Slope::new(horizontal, vertical);
+            assert_eq!(
+                map.trace(&Walk::new(slope))?.trees(),
+                super::count_trees(&map, &slope)?
+            );
+        }
+
+        let trace = map.trace(&Walk::new(Slope::new(3, 1)))?;
+        assert_eq!(trace.visits().len(), 11);
+        assert_eq!(
+            trace.visits()[1],
+            Visit {
+                x: 3,
+                y: 1,
+                location: Location::Open
+            }
+        );
+        assert_eq!(
+            trace.to_string(),
+            "O.##.......
+#..O#...#..
+.#....X..#.
+..#.#...#O#
+.X...##..#.
+..#.X#.....
+.#.#.#.O..#
+.#........X
+#.X#...#...
+#...#X....#
+.#..#...X.#
+"
+        );
+
+        let walk = Walk {
+            stop: Stop::Steps(30),
+            wrap_vertically: true,
+            ..Walk::new(Slope::new(3, 1))
+        };
+        let visits = map.trace(&walk)?.visits().to_vec();
+        assert_eq!(visits.len(), 31);
+        assert_eq!((visits[30].x, visits[30].y), (2, 8));
+        // Without vertical wrap the path still ends at the bottom
+        let walk = Walk {
+            wrap_vertically: false,
+            ..walk
+        };
+        assert_eq!(map.trace(&walk)?.visits().len(), 11);
+
+        let walk = Walk {
+            stop: Stop::Revisit,
+            wrap_vertically: true,
+            ..Walk::new(Slope::new(3, 1))
+        };
+        assert_eq!(map.trace(&walk)?.visits().len(), 11);
+        let walk = Walk {
+            stop: Stop::Revisit,
+            ..Walk::new(Slope::new(-1, 0))
+        };
+        assert_eq!(map.trace(&walk)?.visits().len(), 11);
+
+        assert_eq!(map.trace(&Walk::new(Slope::new(1, -1)))?.visits().len(), 1);
+        assert!(matches!(
+            map.trace(&Walk::new(Slope::new(1, 0))),
+            Err(Errors::FlatSlope)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_map() {
+        let error = super::challenge1("..#\n.x.").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid map: Line 2, column 2: Invalid character 'x', expected '.' or '#'"
+        );
     }
 }
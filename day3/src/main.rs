@@ -1,6 +0,0 @@
-fn main() {
-    let input = include_str!("input.txt");
-    println!("Challenge 1: {}", day3::challenge1(input));
-
-    println!("Challenge 2: {}", day3::challenge2(input));
-}
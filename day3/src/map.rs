@@ -0,0 +1,125 @@
+use std::str::FromStr;
+use utils::{Diagnostic, Grid};
+
+use crate::{Errors, Location, Slope};
+
+/// Map of the area, each row packed in 64 bit words with a bit set for each tree
+///
+/// The map repeats to the right: any column can be looked up and wraps around the width.
+///
+/// # Examples
+///
+/// ```
+/// let map: day3::TreeMap = "..#\n#..".parse().unwrap();
+/// assert!(map.is_tree(2, 0));
+/// assert!(map.is_tree(3, 1));
+/// assert!(!map.is_tree(4, 1));
+/// assert!(!map.is_tree(0, 2));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeMap {
+    words: Vec<u64>,
+    words_per_row: usize,
+    width: usize,
+    height: usize,
+}
+
+impl TreeMap {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether there is a tree in the column `x` of the row `y`, `false` below the last row
+    pub fn is_tree(&self, x: usize, y: usize) -> bool {
+        if y >= self.height || self.width == 0 {
+            return false;
+        }
+
+        let x = x % self.width;
+        self.words[y * self.words_per_row + x / 64] >> (x % 64) & 1 == 1
+    }
+
+    /// Trees encountered on each of the slopes, going over the rows once for all of them
+    ///
+    /// The slopes going up leave the map right after the top left square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use day3::{Slope, TreeMap};
+    ///
+    /// let map: TreeMap = "#..\n.#.\n..#\n#..".parse().unwrap();
+    /// let trees = map.count_trees(&[Slope::new(1, 1), Slope::new(2, 1), Slope::new(0, 3)]);
+    /// assert_eq!(trees.unwrap(), vec![4, 2, 2]);
+    /// ```
+    pub fn count_trees(&self, slopes: &[Slope]) -> Result<Vec<usize>, Errors> {
+        if slopes.iter().any(|slope| slope.vertical == 0) {
+            return Err(Errors::FlatSlope);
+        }
+
+        // Keep the columns wrapped as we go so steep slopes on tall maps can't overflow
+        let shifts: Vec<usize> = slopes
+            .iter()
+            .map(|slope| {
+                slope
+                    .horizontal
+                    .checked_rem_euclid(self.width as isize)
+                    .unwrap_or(0) as usize
+            })
+            .collect();
+        let mut columns = vec![0; slopes.len()];
+        let mut trees = vec![0; slopes.len()];
+        for row in 0..self.height {
+            for (index, slope) in slopes.iter().enumerate() {
+                let on_path = if slope.vertical > 0 {
+                    row % slope.vertical as usize == 0
+                } else {
+                    row == 0
+                };
+                if !on_path {
+                    continue;
+                }
+                if self.is_tree(columns[index], row) {
+                    trees[index] += 1;
+                }
+                columns[index] = (columns[index] + shifts[index]) % self.width.max(1);
+            }
+        }
+
+        Ok(trees)
+    }
+}
+
+impl From<&Grid<Location>> for TreeMap {
+    fn from(grid: &Grid<Location>) -> Self {
+        let (width, height) = (grid.width(), grid.height());
+        let words_per_row = width.div_ceil(64);
+        let mut words = vec![0; words_per_row * height];
+        for y in 0..height {
+            for x in 0..width {
+                if grid.get(y, x) == Some(&Location::Tree) {
+                    words[y * words_per_row + x / 64] |= 1 << (x % 64);
+                }
+            }
+        }
+
+        Self {
+            words,
+            words_per_row,
+            width,
+            height,
+        }
+    }
+}
+
+impl FromStr for TreeMap {
+    type Err = Diagnostic;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(&input.parse::<Grid<Location>>()?))
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+use utils::Grid;
+
+use crate::{Errors, Location, Slope, TreeMap};
+
+/// When a walk over the map ends
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Stop {
+    /// Once the path would go past the last row, or above the first one for upward slopes, even
+    /// if the map wraps vertically
+    Bottom,
+    /// After this many moves, or when the path leaves a map that does not wrap vertically
+    Steps(usize),
+    /// Before the first square visited twice, or when the path leaves a map that does not wrap
+    /// vertically
+    Revisit,
+}
+
+/// Path from the top left square following a slope
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Walk {
+    pub slope: Slope,
+    pub stop: Stop,
+    /// Whether the map also repeats downwards and upwards
+    pub wrap_vertically: bool,
+}
+
+impl Walk {
+    /// Walk down to the bottom of the map
+    pub fn new(slope: Slope) -> Self {
+        Self {
+            slope,
+            stop: Stop::Bottom,
+            wrap_vertically: false,
+        }
+    }
+}
+
+/// A square of the map the path went through
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Visit {
+    /// Column, wrapped into the map
+    pub x: usize,
+    /// Row, wrapped into the map
+    pub y: usize,
+    pub location: Location,
+}
+
+/// Squares a walk went through, in order
+///
+/// Displays as the map with the visited open squares as `O` and the visited trees as `X`.
+#[derive(Clone, Debug)]
+pub struct Trace<'m> {
+    map: &'m TreeMap,
+    visits: Vec<Visit>,
+}
+
+impl Trace<'_> {
+    pub fn visits(&self) -> &[Visit] {
+        &self.visits
+    }
+
+    /// Number of trees encountered
+    pub fn trees(&self) -> usize {
+        self.visits
+            .iter()
+            .filter(|visit| visit.location == Location::Tree)
+            .count()
+    }
+}
+
+impl fmt::Display for Trace<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut grid = Grid::new(self.map.height(), self.map.width(), '.');
+        for y in 0..self.map.height() {
+            for x in 0..self.map.width() {
+                if self.map.is_tree(x, y) {
+                    if let Some(cell) = grid.get_mut(y, x) {
+                        *cell = '#';
+                    }
+                }
+            }
+        }
+        for visit in &self.visits {
+            if let Some(cell) = grid.get_mut(visit.y, visit.x) {
+                *cell = match visit.location {
+                    Location::Tree => 'X',
+                    Location::Open => 'O',
+                };
+            }
+        }
+
+        write!(f, "{}", grid)
+    }
+}
+
+impl TreeMap {
+    /// Follow the walk from the top left square, the map always repeats to the right and left
+    ///
+    /// Fails with [`Errors::FlatSlope`] for a walk to the bottom on a slope that never moves up or
+    /// down, it would not get there.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use day3::{Slope, Stop, TreeMap, Walk};
+    ///
+    /// let map: TreeMap = "..#\n#..\n.#.".parse().unwrap();
+    /// let walk = Walk {
+    ///     stop: Stop::Revisit,
+    ///     wrap_vertically: true,
+    ///     ..Walk::new(Slope::new(-1, -1))
+    /// };
+    /// let trace = map.trace(&walk).unwrap();
+    /// assert_eq!(trace.visits().len(), 3);
+    /// assert_eq!(trace.trees(), 0);
+    /// assert_eq!(trace.to_string(), "O.#\n#O.\n.#O\n");
+    /// ```
+    pub fn trace(&self, walk: &Walk) -> Result<Trace<'_>, Errors> {
+        let Walk {
+            slope,
+            stop,
+            wrap_vertically,
+        } = *walk;
+        if slope.vertical == 0 && stop == Stop::Bottom {
+            return Err(Errors::FlatSlope);
+        }
+
+        let mut visits = Vec::new();
+        let (width, height) = (self.width(), self.height());
+        if width == 0 || height == 0 {
+            return Ok(Trace { map: self, visits });
+        }
+
+        let wraps = wrap_vertically && stop != Stop::Bottom;
+        let shift = slope.horizontal.rem_euclid(width as isize) as usize;
+        let mut seen = match stop {
+            Stop::Revisit => vec![false; width * height],
+            _ => Vec::new(),
+        };
+        let (mut x, mut y) = (0, 0);
+        loop {
+            if stop == Stop::Revisit {
+                if seen[y * width + x] {
+                    break;
+                }
+                seen[y * width + x] = true;
+            }
+            let location = if self.is_tree(x, y) {
+                Location::Tree
+            } else {
+                Location::Open
+            };
+            visits.push(Visit { x, y, location });
+            if let Stop::Steps(steps) = stop {
+                if visits.len() > steps {
+                    break;
+                }
+            }
+
+            x = (x + shift) % width;
+            y = if wraps {
+                (y + slope.vertical.rem_euclid(height as isize) as usize) % height
+            } else {
+                match y.checked_add_signed(slope.vertical).filter(|&y| y < height) {
+                    Some(y) => y,
+                    None => break,
+                }
+            };
+        }
+
+        Ok(Trace { map: self, visits })
+    }
+}
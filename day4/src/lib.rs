@@ -1,29 +1,59 @@
-use std::{collections::HashMap, iter};
-
-fn parse_passports(input: &str) -> impl Iterator<Item = HashMap<&str, &str>> {
-    let mut passports = Vec::new();
-
-    input
-        .lines()
-        .chain(iter::once(""))
-        .fold(HashMap::new(), |mut state, line| {
-            if line.is_empty() {
-                passports.push(state);
-                return HashMap::new();
-            }
+use displaydoc::Display;
+use std::collections::HashMap;
+use thiserror::Error;
+use utils::{Diagnostic, Solution};
+
+#[derive(Debug, Error, Display)]
+pub enum Errors {
+    /// Invalid input: {0}
+    Invalid(#[from] Diagnostic),
+    /// Expected a field like "key:value"
+    InvalidField,
+}
 
-            state.extend(line.split_whitespace().map(|segment| {
-                if let [key, value] = segment.split(':').collect::<Vec<&str>>()[..] {
-                    (key, value)
-                } else {
-                    panic!("Invalid segment \"{}\"", segment);
-                }
-            }));
+/// Passport fields indexed by their key
+pub type Passport<'i> = HashMap<&'i str, &'i str>;
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input<'i> = Vec<Passport<'i>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse_passports(input)?)
+    }
 
-            state
-        });
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(count_complete(input))
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(count_valid(input))
+    }
+}
+
+fn parse_passports(input: &str) -> Result<Vec<Passport<'_>>, Diagnostic> {
+    utils::records(input)
+        .map(|record| {
+            let mut passport = Passport::new();
+            for (index, line) in record.lines.iter().enumerate() {
+                for field in line.split_whitespace() {
+                    let (key, value) = field
+                        .split_once(':')
+                        .filter(|(_, value)| !value.contains(':'))
+                        .ok_or_else(|| {
+                            Diagnostic::new(line, field, Errors::InvalidField)
+                                .on_line(record.line + index)
+                        })?;
+                    passport.insert(key, value);
+                }
+            }
 
-    passports.into_iter()
+            Ok(passport)
+        })
+        .collect()
 }
 
 macro_rules! filter_invalid_fields {
@@ -54,34 +84,34 @@ mod validators {
     pub fn byr(value: &str) -> bool {
         value
             .parse::<u16>()
-            .map(|value| 1920 <= value && value <= 2002)
+            .map(|value| (1920..=2002).contains(&value))
             .unwrap_or(false)
     }
 
     pub fn iyr(value: &str) -> bool {
         value
             .parse::<u16>()
-            .map(|value| 2010 <= value && value <= 2020)
+            .map(|value| (2010..=2020).contains(&value))
             .unwrap_or(false)
     }
 
     pub fn eyr(value: &str) -> bool {
         value
             .parse::<u16>()
-            .map(|value| 2010 <= value && value <= 2030)
+            .map(|value| (2010..=2030).contains(&value))
             .unwrap_or(false)
     }
 
     pub fn hgt(value: &str) -> bool {
-        if value.ends_with("cm") {
-            value[..value.len() - 2]
+        if let Some(value) = value.strip_suffix("cm") {
+            value
                 .parse::<u16>()
-                .map(|value| 150 <= value && value <= 193)
+                .map(|value| (150..=193).contains(&value))
                 .unwrap_or(false)
-        } else if value.ends_with("in") {
-            value[..value.len() - 2]
+        } else if let Some(value) = value.strip_suffix("in") {
+            value
                 .parse::<u16>()
-                .map(|value| 59 <= value && value <= 76)
+                .map(|value| (59..=76).contains(&value))
                 .unwrap_or(false)
         } else {
             false
@@ -89,7 +119,9 @@ mod validators {
     }
 
     pub fn hcl(value: &str) -> bool {
-        value.starts_with('#') && value.len() == 7 && value[1..].chars().all(|c| c.is_digit(16))
+        value.starts_with('#')
+            && value.len() == 7
+            && value[1..].chars().all(|c| c.is_ascii_hexdigit())
     }
 
     pub fn ecl(value: &str) -> bool {
@@ -101,12 +133,20 @@ mod validators {
     }
 }
 
-pub fn challenge1(input: &str) -> usize {
-    filter_missing_fields!(parse_passports(input), byr, iyr, eyr, hgt, hcl, ecl, pid).count()
+fn count_complete(passports: &[Passport<'_>]) -> usize {
+    filter_missing_fields!(passports.iter(), byr, iyr, eyr, hgt, hcl, ecl, pid).count()
+}
+
+fn count_valid(passports: &[Passport<'_>]) -> usize {
+    filter_invalid_fields!(passports.iter(), byr, iyr, eyr, hgt, hcl, ecl, pid).count()
 }
 
-pub fn challenge2(input: &str) -> usize {
-    filter_invalid_fields!(parse_passports(input), byr, iyr, eyr, hgt, hcl, ecl, pid).count()
+pub fn challenge1(input: &str) -> Result<usize, Errors> {
+    Ok(count_complete(&parse_passports(input)?))
+}
+
+pub fn challenge2(input: &str) -> Result<usize, Errors> {
+    Ok(count_valid(&parse_passports(input)?))
 }
 
 #[cfg(test)]
@@ -126,31 +166,51 @@ hcl:#cfa07d eyr:2025 pid:166559648
 iyr:2011 ecl:brn hgt:59in"#;
 
     #[test]
-    fn test_challenge1() {
-        assert_eq!(super::challenge1(INPUT), 2);
+    fn test_challenge1() -> Result<(), super::Errors> {
+        assert_eq!(super::challenge1(INPUT)?, 2);
+        Ok(())
     }
 
     #[test]
-    fn test_challenge2() {
-        assert_eq!(super::challenge2(INPUT), 2);
+    fn test_challenge2() -> Result<(), super::Errors> {
+        assert_eq!(super::challenge2(INPUT)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_crlf() -> Result<(), super::Errors> {
+        let input = INPUT.replace('\n', " \r\n");
+        assert_eq!(super::challenge1(&input)?, 2);
+        assert_eq!(super::challenge2(&input)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_field() {
+        match super::challenge1("ecl:gry\n\nbyr:1937\niyr:2017 cid147") {
+            Err(super::Errors::Invalid(diagnostic)) => {
+                assert_eq!((diagnostic.line(), diagnostic.columns()), (4, 10..16));
+            }
+            _ => panic!("Invalid field was accepted"),
+        }
     }
 
     #[test]
     fn test_validators() {
         use super::validators::*;
 
-        assert_eq!(byr("2002"), true, "byr valid:   2002");
-        assert_eq!(byr("2003"), false, "byr invalid: 2003");
-        assert_eq!(hgt("60in"), true, "hgt valid:   60in");
-        assert_eq!(hgt("190cm"), true, "hgt valid:   190cm");
-        assert_eq!(hgt("190in"), false, "hgt invalid: 190in");
-        assert_eq!(hgt("190"), false, "hgt invalid: 190");
-        assert_eq!(hcl("#123abc"), true, "hcl valid:   #123abc");
-        assert_eq!(hcl("#123abz"), false, "hcl invalid: #123abz");
-        assert_eq!(hcl("123abc"), false, "hcl invalid: 123abc");
-        assert_eq!(ecl("brn"), true, "ecl valid:   brn");
-        assert_eq!(ecl("wat"), false, "ecl invalid: wat");
-        assert_eq!(pid("000000001"), true, "pid valid:   000000001");
-        assert_eq!(pid("0123456789"), false, "pid invalid: 0123456789");
+        assert!(byr("2002"), "byr valid:   2002");
+        assert!(!byr("2003"), "byr invalid: 2003");
+        assert!(hgt("60in"), "hgt valid:   60in");
+        assert!(hgt("190cm"), "hgt valid:   190cm");
+        assert!(!hgt("190in"), "hgt invalid: 190in");
+        assert!(!hgt("190"), "hgt invalid: 190");
+        assert!(hcl("#123abc"), "hcl valid:   #123abc");
+        assert!(!hcl("#123abz"), "hcl invalid: #123abz");
+        assert!(!hcl("123abc"), "hcl invalid: 123abc");
+        assert!(ecl("brn"), "ecl valid:   brn");
+        assert!(!ecl("wat"), "ecl invalid: wat");
+        assert!(pid("000000001"), "pid valid:   000000001");
+        assert!(!pid("0123456789"), "pid invalid: 0123456789");
     }
 }
@@ -1,6 +0,0 @@
-fn main() {
-    let input = include_str!("input.txt");
-    println!("Challenge 1: {}", day4::challenge1(input));
-
-    println!("Challenge 2: {}", day4::challenge2(input));
-}
@@ -1,11 +1,71 @@
-use std::collections::BTreeSet;
+use displaydoc::Display;
+use std::{collections::BTreeSet, io::BufRead};
+use thiserror::Error;
+use utils::{Diagnostic, ReadError, Solution};
 
-pub fn challenge1(input: &str) -> u16 {
-    input.lines().map(parse_seat_id).max().unwrap_or(0)
+#[derive(Debug, Error, Display)]
+pub enum Errors {
+    /// Invalid input: {0}
+    Invalid(#[from] Diagnostic),
+    /// {0}
+    Read(#[from] ReadError),
+    /// Invalid character {0:?}, expected 'F' or 'B' for the row then 'L' or 'R' for the column
+    InvalidCharacter(char),
+    /// Seat is {0} characters long, expected 10
+    BadLength(usize),
 }
 
-pub fn challenge2(input: &str) -> u16 {
-    let assignments: BTreeSet<u16> = input.lines().map(parse_seat_id).collect();
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input<'i> = Vec<u16>;
+    type Answer1 = u16;
+    type Answer2 = u16;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(highest_seat(input))
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(missing_seat(input.iter().copied()))
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<u16>, Diagnostic> {
+    utils::parse_lines(input, parse_seat_id)
+}
+
+pub fn challenge1(input: &str) -> Result<u16, Errors> {
+    Ok(highest_seat(&parse(input)?))
+}
+
+pub fn challenge2(input: &str) -> Result<u16, Errors> {
+    Ok(missing_seat(parse(input)?))
+}
+
+/// [`challenge1`] reading one boarding pass at a time
+pub fn challenge1_from_reader(reader: impl BufRead) -> Result<u16, Errors> {
+    Ok(utils::read_lines(reader, parse_seat_id)
+        .try_fold(0, |highest, seat| seat.map(|seat| highest.max(seat)))?)
+}
+
+/// [`challenge2`] reading one boarding pass at a time, there are at most 1024 seats to remember
+pub fn challenge2_from_reader(reader: impl BufRead) -> Result<u16, Errors> {
+    let seats: BTreeSet<u16> =
+        utils::read_lines(reader, parse_seat_id).collect::<Result<_, _>>()?;
+    Ok(missing_seat(seats))
+}
+
+fn highest_seat(seats: &[u16]) -> u16 {
+    seats.iter().copied().max().unwrap_or(0)
+}
+
+fn missing_seat(seats: impl IntoIterator<Item = u16>) -> u16 {
+    let assignments: BTreeSet<u16> = seats.into_iter().collect();
 
     let mut last_seat = assignments.iter().next().copied().unwrap_or(0);
     for &seat in assignments.iter().skip(1) {
@@ -19,11 +79,23 @@ pub fn challenge2(input: &str) -> u16 {
     last_seat + 1
 }
 
-pub fn parse_seat_id(line: &str) -> u16 {
-    line.chars().fold(0, |acc, c| match c {
-        'B' | 'R' => (acc << 1) | 1,
-        _ => acc << 1,
-    })
+/// Binary space partitioned seat, 7 characters for the row followed by 3 for the column
+pub fn parse_seat_id(line: &str) -> Result<u16, Diagnostic> {
+    let length = line.chars().count();
+    if length != 10 {
+        return Err(Diagnostic::new(line, line, Errors::BadLength(length)));
+    }
+
+    line.char_indices()
+        .try_fold(0, |acc, (index, c)| match (index < 7, c) {
+            (true, 'B') | (false, 'R') => Ok((acc << 1) | 1),
+            (true, 'F') | (false, 'L') => Ok(acc << 1),
+            _ => Err(Diagnostic::new(
+                line,
+                &line[index..index + c.len_utf8()],
+                Errors::InvalidCharacter(c),
+            )),
+        })
 }
 
 #[cfg(test)]
@@ -34,15 +106,43 @@ FFFBBBFRRR
 BBFFBBFRLL"#;
 
     #[test]
-    fn test_seat_id() {
-        assert_eq!(super::parse_seat_id("FBFBBFFRLR"), 357);
-        assert_eq!(super::parse_seat_id("BFFFBBFRRR"), 567);
-        assert_eq!(super::parse_seat_id("FFFBBBFRRR"), 119);
-        assert_eq!(super::parse_seat_id("BBFFBBFRLL"), 820);
+    fn test_seat_id() -> Result<(), super::Errors> {
+        assert_eq!(super::parse_seat_id("FBFBBFFRLR")?, 357);
+        assert_eq!(super::parse_seat_id("BFFFBBFRRR")?, 567);
+        assert_eq!(super::parse_seat_id("FFFBBBFRRR")?, 119);
+        assert_eq!(super::parse_seat_id("BBFFBBFRLL")?, 820);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_seat() {
+        let error = super::challenge1("FBFBBFFRLR\nFBFBBFRRLR").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid input: Line 2, column 7: Invalid character 'R', expected 'F' or 'B' for the row then 'L' or 'R' for the column"
+        );
+    }
+
+    #[test]
+    fn test_challenge1() -> Result<(), super::Errors> {
+        assert_eq!(super::challenge1(INPUT)?, 820);
+        assert_eq!(super::challenge1_from_reader(INPUT.as_bytes())?, 820);
+        Ok(())
     }
 
     #[test]
-    fn test_challenge1() {
-        assert_eq!(super::challenge1(INPUT), 820);
+    fn test_challenge2() -> Result<(), super::Errors> {
+        let input = "FFFFFFFLLR\nFFFFFFFLRR\nFFFFFFFRLL";
+        assert_eq!(super::challenge2(input)?, 2);
+        assert_eq!(super::challenge2_from_reader(input.as_bytes())?, 2);
+        Ok(())
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_planted(planted in generators::day5::boarding_passes()) {
+            proptest::prop_assert_eq!(super::challenge1(&planted.input)?, planted.part1);
+            proptest::prop_assert_eq!(super::challenge2(&planted.input)?, planted.part2);
+        }
     }
 }
@@ -1,6 +0,0 @@
-fn main() {
-    let input = include_str!("input.txt");
-    println!("Challenge 1: {}", day5::challenge1(input));
-
-    println!("Challenge 2: {}", day5::challenge2(input));
-}
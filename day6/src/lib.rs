@@ -1,49 +1,67 @@
 use std::collections::HashSet;
-use std::iter;
+use utils::{Record, Solution};
 
-pub fn challenge1(input: &str) -> usize {
-    let mut census = Vec::new();
-    input
-        .lines()
-        .chain(iter::once(""))
-        .fold(HashSet::new(), |mut answers, line| {
-            if line.is_empty() {
-                census.push(answers.len());
-                HashSet::new()
-            } else {
-                answers.extend(line.chars());
-                answers
-            }
-        });
-
-    census.iter().sum()
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input<'i> = Vec<Record<'i>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(utils::records(input).collect())
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(input.iter().map(anyone_count).sum())
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(input.iter().map(everyone_count).sum())
+    }
 }
 
-pub fn challenge2(input: &str) -> usize {
-    let mut census = Vec::new();
-    input
-        .lines()
-        .chain(iter::once(""))
+/// Number of questions anyone in the group answered yes to
+fn anyone_count(group: &Record<'_>) -> usize {
+    group
+        .lines
+        .iter()
+        .flat_map(|line| line.chars())
+        .collect::<HashSet<char>>()
+        .len()
+}
+
+/// Number of questions everyone in the group answered yes to
+fn everyone_count(group: &Record<'_>) -> usize {
+    group
+        .lines
+        .iter()
         .map(|line| line.chars().collect::<HashSet<char>>())
         .fold(
             None,
             |previous_answers: Option<HashSet<char>>, line_answers| match previous_answers {
-                Some(previous_answers) if line_answers.is_empty() => {
-                    census.push(previous_answers.len());
-                    None
-                }
                 Some(previous_answers) => Some(
                     line_answers
                         .intersection(&previous_answers)
                         .copied()
                         .collect(),
                 ),
-                None if line_answers.is_empty() => None,
                 None => Some(line_answers),
             },
-        );
+        )
+        .map_or(0, |answers| answers.len())
+}
 
-    census.iter().sum()
+pub fn challenge1(input: &str) -> usize {
+    utils::records(input)
+        .map(|group| anyone_count(&group))
+        .sum()
+}
+
+pub fn challenge2(input: &str) -> usize {
+    utils::records(input)
+        .map(|group| everyone_count(&group))
+        .sum()
 }
 
 #[cfg(test)]
@@ -73,4 +91,13 @@ b"#;
     fn test_challenge2() {
         assert_eq!(super::challenge2(INPUT), 6);
     }
+
+    #[test]
+    fn test_crlf() {
+        let input = INPUT
+            .replace('\n', "\r\n")
+            .replace("\r\n\r\n", "\r\n  \r\n");
+        assert_eq!(super::challenge1(&input), 11);
+        assert_eq!(super::challenge2(&input), 6);
+    }
 }
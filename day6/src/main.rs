@@ -1,6 +0,0 @@
-fn main() {
-    let input = include_str!("input.txt");
-    println!("Challenge 1: {}", day6::challenge1(input));
-
-    println!("Challenge 2: {}", day6::challenge2(input));
-}
@@ -5,110 +5,160 @@ use std::{
     convert::TryFrom,
 };
 use thiserror::Error;
+use utils::{Diagnostic, Solution};
 
-struct Rule<'a> {
+/// Bag rule, the bag color and the number of each bag color it must contain
+pub struct Rule<'a> {
     bag: &'a str,
     contents: HashMap<&'a str, u32>,
 }
 
 #[derive(Debug, Error, Display)]
 pub enum Errors {
+    /// Invalid input: {0}
+    Invalid(#[from] Diagnostic),
     /// Parse error {0}
     ParseError(#[from] std::num::ParseIntError),
-    /// Bad line {0}
-    BadLine(String),
+    /// Expected a rule like "<color> bags contain <contents>"
+    BadLine,
     /// Bad regex {0}
     BadRegex(#[from] regex::Error),
     /// Missing placeholder {0}
     MissingPlaceholder(&'static str),
     /// Bag bag {0}
     UnknownBag(String),
+    /// Bag {0} ends up containing itself
+    Cycle(String),
+    /// Answer does not fit in 32 bits
+    Overflow,
 }
 
 impl<'a> TryFrom<&'a str> for Rule<'a> {
-    type Error = Errors;
+    type Error = Diagnostic;
 
     fn try_from(line: &'a str) -> Result<Self, Self::Error> {
-        let re = Regex::new(r"(?P<count>[[:digit:]]+) (?P<color>[[:alpha:]]+ [[:alpha:]]+) bag")?;
+        let re = Regex::new(r"(?P<count>[[:digit:]]+) (?P<color>[[:alpha:]]+ [[:alpha:]]+) bag")
+            .map_err(|e| Diagnostic::new(line, line, Errors::from(e)))?;
 
         match line.split(" bags contain ").collect::<Vec<_>>()[..] {
             [bag, contains] => {
                 let contents = re
                     .captures_iter(contains)
                     .map(|captures| {
+                        let placeholder = |name| {
+                            captures.name(name).map(|m| m.as_str()).ok_or_else(|| {
+                                Diagnostic::new(line, contains, Errors::MissingPlaceholder(name))
+                            })
+                        };
+                        let count = placeholder("count")?;
+
                         Ok((
-                            captures
-                                .name("color")
-                                .ok_or(Errors::MissingPlaceholder("color"))?
-                                .as_str(),
-                            captures
-                                .name("count")
-                                .ok_or(Errors::MissingPlaceholder("count"))?
-                                .as_str()
-                                .parse()?,
+                            placeholder("color")?,
+                            count
+                                .parse()
+                                .map_err(|e| Diagnostic::new(line, count, Errors::from(e)))?,
                         ))
                     })
-                    .collect::<Result<_, Errors>>()?;
+                    .collect::<Result<_, Diagnostic>>()?;
 
                 Ok(Rule { bag, contents })
             }
-            _ => Err(Errors::BadLine(line.to_string())),
+            _ => Err(Diagnostic::new(line, line, Errors::BadLine)),
         }
     }
 }
 
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input<'i> = Vec<Rule<'i>>;
+    type Answer1 = usize;
+    type Answer2 = u32;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(count_containing(input))
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(count_contained(input)?)
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<Rule<'_>>, Diagnostic> {
+    utils::parse_lines(input, Rule::try_from)
+}
+
 pub fn challenge1(input: &str) -> Result<usize, Errors> {
-    let rules = input
-        .lines()
-        .map(Rule::try_from)
-        .collect::<Result<Vec<Rule>, _>>()?;
+    Ok(count_containing(&parse(input)?))
+}
 
+fn count_containing(rules: &[Rule<'_>]) -> usize {
     let containing: HashMap<&str, HashSet<&str>> =
-        rules.into_iter().fold(HashMap::new(), |mut acc, rule| {
+        rules.iter().fold(HashMap::new(), |mut acc, rule| {
             let Rule { bag, contents } = rule;
-            contents.into_iter().for_each(|(contained, _count)| {
+            contents.keys().for_each(|&contained| {
                 acc.entry(contained)
                     .or_insert_with(HashSet::new)
-                    .insert(bag);
+                    .insert(*bag);
             });
             acc
         });
 
-    fn recurse<'m>(map: &'m HashMap<&str, HashSet<&str>>, target: &str) -> HashSet<&'m str> {
-        map.get(target)
-            .into_iter()
-            .flat_map(|bags| {
-                bags.iter()
-                    .copied()
-                    .chain(bags.iter().flat_map(|bag| recurse(map, bag)))
-            })
-            .collect()
+    // Walk up the containers without recursing, rules can contain cycles
+    let mut found = HashSet::new();
+    let mut pending = vec!["shiny gold"];
+    while let Some(target) = pending.pop() {
+        for &bag in containing.get(target).into_iter().flatten() {
+            if found.insert(bag) {
+                pending.push(bag);
+            }
+        }
     }
 
-    Ok(recurse(&containing, "shiny gold").len())
+    found.len()
 }
 
 pub fn challenge2(input: &str) -> Result<u32, Errors> {
-    let index: HashMap<&str, Rule> = input
-        .lines()
-        .map(Rule::try_from)
-        .map(|result| result.map(|rule| (rule.bag, rule)))
-        .collect::<Result<_, _>>()?;
+    count_contained(&parse(input)?)
+}
+
+fn count_contained(rules: &[Rule<'_>]) -> Result<u32, Errors> {
+    let index: HashMap<&str, &Rule> = rules.iter().map(|rule| (rule.bag, rule)).collect();
+
+    // Totals are memoized, `None` marks the bags being counted to detect cycles
+    fn recurse<'r>(
+        index: &HashMap<&str, &Rule<'r>>,
+        totals: &mut HashMap<&'r str, Option<u32>>,
+        target: &'r str,
+    ) -> Result<u32, Errors> {
+        match totals.get(target) {
+            Some(Some(total)) => return Ok(*total),
+            Some(None) => return Err(Errors::Cycle(target.to_string())),
+            None => totals.insert(target, None),
+        };
 
-    fn recurse<'m>(index: &'m HashMap<&str, Rule>, target: &str) -> Result<u32, Errors> {
         let rule = index
             .get(target)
             .ok_or_else(|| Errors::UnknownBag(target.to_string()))?;
 
-        let mut sum = 0;
-        for (bag, count) in rule.contents.iter() {
-            sum += count + count * recurse(index, bag)?;
+        let mut sum: u32 = 0;
+        for (&bag, &count) in rule.contents.iter() {
+            sum = recurse(index, totals, bag)?
+                .checked_add(1)
+                .and_then(|inner| inner.checked_mul(count))
+                .and_then(|bags| bags.checked_add(sum))
+                .ok_or(Errors::Overflow)?;
         }
 
+        totals.insert(target, Some(sum));
         Ok(sum)
     }
 
-    recurse(&index, "shiny gold")
+    recurse(&index, &mut HashMap::new(), "shiny gold")
 }
 
 #[cfg(test)]
@@ -130,6 +180,19 @@ dotted black bags contain no other bags."#;
         Ok(())
     }
 
+    #[test]
+    fn test_cycle() -> Result<(), super::Errors> {
+        const INPUT: &str = r#"shiny gold bags contain 1 dark red bag.
+dark red bags contain 2 shiny gold bags."#;
+
+        assert_eq!(super::challenge1(INPUT)?, 2);
+        assert!(matches!(
+            super::challenge2(INPUT),
+            Err(super::Errors::Cycle(_))
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_challenge2() -> Result<(), super::Errors> {
         const INPUT: &str = r#"shiny gold bags contain 2 dark red bags.
@@ -1,22 +1,30 @@
 use displaydoc::Display;
-use itertools::Itertools as _;
-use std::{collections::HashSet, str::FromStr};
+use std::{collections::HashSet, io::BufRead, str::FromStr};
 use thiserror::Error;
+use utils::{Diagnostic, ReadError, Solution};
 
 #[derive(Debug, Error, Display)]
 pub enum Errors {
+    /// Invalid input: {0}
+    Invalid(#[from] Diagnostic),
+    /// {0}
+    Read(#[from] ReadError),
     /// Parse error {0}
     ParseError(#[from] std::num::ParseIntError),
     /// Bad regex {0}
     BadRegex(#[from] regex::Error),
     /// "{0}" is not a valid instruction
     InvalidInstruction(String),
+    /// Expected an instruction like "acc +1"
+    BadLine,
     /// Out of bounds, "{0}" is not in program memory
     InstructionOutOfBounds(usize),
     /// Out of bounds, could not make negative program memory
     InstructionUnderflow,
     /// Infinite loop
     InfiniteLoop(ProgramState),
+    /// Accumulator overflowed
+    AccumulatorOverflow,
 }
 
 #[derive(Debug, Clone)]
@@ -37,7 +45,7 @@ pub struct ProgramState {
 }
 
 impl ProgramState {
-    fn run_one_instruction(mut self, program: &Vec<Instruction>) -> Result<Self, Errors> {
+    fn run_one_instruction(mut self, program: &[Instruction]) -> Result<Self, Errors> {
         let instruction = program
             .get(self.instruction_ptr)
             .ok_or(Errors::InstructionOutOfBounds(self.instruction_ptr))?;
@@ -50,24 +58,24 @@ impl ProgramState {
             Instruction::NoOp(..) => self.instruction_ptr += 1,
             Instruction::Accumulator(arg) => {
                 self.instruction_ptr += 1;
-                self.accumulator += arg
+                self.accumulator = self
+                    .accumulator
+                    .checked_add(*arg)
+                    .ok_or(Errors::AccumulatorOverflow)?;
             }
             Instruction::Jump(arg) => {
-                if *arg < 0 {
-                    if -arg as usize > self.instruction_ptr {
-                        return Err(Errors::InstructionUnderflow);
-                    }
-                    self.instruction_ptr -= -arg as usize
-                } else {
-                    self.instruction_ptr += *arg as usize
-                }
+                // The pointer is within the program so only jumping backward can leave the usize range
+                self.instruction_ptr = self
+                    .instruction_ptr
+                    .checked_add_signed(*arg)
+                    .ok_or(Errors::InstructionUnderflow)?;
             }
         }
 
         Ok(self)
     }
 
-    pub fn run(mut self, program: &Vec<Instruction>) -> Result<Self, Errors> {
+    pub fn run(mut self, program: &[Instruction]) -> Result<Self, Errors> {
         loop {
             self = self.run_one_instruction(program)?;
             if self.instruction_ptr == program.len() {
@@ -79,28 +87,58 @@ impl ProgramState {
 }
 
 impl FromStr for Instruction {
-    type Err = Errors;
+    type Err = Diagnostic;
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        if value.len() < 5 {
-            return Err(Errors::InvalidInstruction(value.to_string()));
-        }
-        let operation = &value[0..3];
-        let argument = value[4..].parse()?;
+        let (operation, argument) = value
+            .split_once(' ')
+            .ok_or_else(|| Diagnostic::new(value, value, Errors::BadLine))?;
+        let argument = argument
+            .parse()
+            .map_err(|e| Diagnostic::new(value, argument, Errors::from(e)))?;
 
         Ok(match operation {
             "acc" => Instruction::Accumulator(argument),
             "jmp" => Instruction::Jump(argument),
             "nop" => Instruction::NoOp(argument),
-            _ => return Err(Errors::InvalidInstruction(value.to_string())),
+            _ => {
+                let error = Errors::InvalidInstruction(operation.to_string());
+                return Err(Diagnostic::new(value, operation, error));
+            }
         })
     }
 }
 
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input<'i> = Vec<Instruction>;
+    type Answer1 = isize;
+    type Answer2 = isize;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(accumulator_before_loop(input)?)
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(repaired_accumulator(input.clone())?)
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<Instruction>, Diagnostic> {
+    utils::parse_lines(input, str::parse)
+}
+
 pub fn challenge1(input: &str) -> Result<isize, Errors> {
-    let program = input.lines().map(str::parse).try_collect()?;
+    accumulator_before_loop(&parse(input)?)
+}
 
-    let state = match ProgramState::default().run(&program) {
+fn accumulator_before_loop(program: &[Instruction]) -> Result<isize, Errors> {
+    let state = match ProgramState::default().run(program) {
         Err(Errors::InfiniteLoop(state)) => state,
         Err(e) => return Err(e),
         Ok(state) => state,
@@ -110,8 +148,25 @@ pub fn challenge1(input: &str) -> Result<isize, Errors> {
 }
 
 pub fn challenge2(input: &str) -> Result<isize, Errors> {
-    let mut program: Vec<Instruction> = input.lines().map(str::parse).try_collect()?;
+    repaired_accumulator(parse(input)?)
+}
+
+/// [`challenge1`] reading one instruction at a time, the program is kept but not its text
+pub fn challenge1_from_reader(reader: impl BufRead) -> Result<isize, Errors> {
+    accumulator_before_loop(&read_program(reader)?)
+}
+
+/// [`challenge2`] reading one instruction at a time, the program is kept but not its text
+pub fn challenge2_from_reader(reader: impl BufRead) -> Result<isize, Errors> {
+    repaired_accumulator(read_program(reader)?)
+}
+
+fn read_program(reader: impl BufRead) -> Result<Vec<Instruction>, ReadError> {
+    utils::read_lines(reader, str::parse).collect()
+}
 
+#[tracing::instrument(level = "debug", skip_all, fields(len = program.len()))]
+fn repaired_accumulator(mut program: Vec<Instruction>) -> Result<isize, Errors> {
     for i in 0..program.len() {
         let compliment_op = match &program[i] {
             Instruction::Jump(arg) => Instruction::NoOp(*arg),
@@ -119,14 +174,16 @@ pub fn challenge2(input: &str) -> Result<isize, Errors> {
             _ => continue,
         };
 
+        tracing::debug!(position = i, "trying a repair");
         let previous_op = std::mem::replace(&mut program[i], compliment_op);
         if let Ok(state) = ProgramState::default().run(&program) {
+            tracing::debug!(position = i, accumulator = state.accumulator, "repaired");
             return Ok(state.accumulator);
         }
         program[i] = previous_op;
     }
 
-    return Ok(0);
+    Ok(0)
 }
 
 #[cfg(test)]
@@ -144,12 +201,22 @@ acc +6"#;
     #[test]
     fn test_challenge1() -> Result<(), super::Errors> {
         assert_eq!(super::challenge1(INPUT)?, 5);
+        assert_eq!(super::challenge1_from_reader(INPUT.as_bytes())?, 5);
         Ok(())
     }
 
     #[test]
     fn test_challenge2() -> Result<(), super::Errors> {
         assert_eq!(super::challenge2(INPUT)?, 8);
+        assert_eq!(super::challenge2_from_reader(INPUT.as_bytes())?, 8);
         Ok(())
     }
+
+    proptest::proptest! {
+        #[test]
+        fn test_planted(planted in generators::day8::corrupted_program()) {
+            proptest::prop_assert_eq!(super::challenge1(&planted.input)?, planted.part1);
+            proptest::prop_assert_eq!(super::challenge2(&planted.input)?, planted.part2);
+        }
+    }
 }
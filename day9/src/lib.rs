@@ -1,55 +1,133 @@
-#![allow(unstable_name_collisions)]
 use displaydoc::Display;
 use itertools::Itertools as _;
 use std::cmp::Ordering;
-use std::ops::Not as _;
+use std::collections::VecDeque;
+use std::io::BufRead;
 use thiserror::Error;
-use utils::BoolExt as _;
+use utils::{Diagnostic, ReadError, Solution};
+
+#[cfg(any(test, feature = "reference"))]
+pub mod reference;
 
 #[derive(Debug, Error, Display)]
 pub enum Errors {
+    /// Invalid input: {0}
+    Invalid(#[from] Diagnostic),
+    /// {0}
+    Read(#[from] ReadError),
     /// Parse error {0}
     ParseError(#[from] std::num::ParseIntError),
     /// No solution was found
     NoSolutionFound,
 }
 
-fn first_invalid(cipher_text: &Vec<u64>, preamble_len: usize) -> Result<u64, Errors> {
-    (preamble_len..cipher_text.len())
-        .find_map(|i| {
-            cipher_text[i - preamble_len..i]
+/// XMAS decoder, each number must be the sum of two of the `preamble_len` numbers before it
+pub struct Day9 {
+    pub preamble_len: usize,
+}
+
+impl Default for Day9 {
+    fn default() -> Self {
+        Self { preamble_len: 25 }
+    }
+}
+
+impl Solution for Day9 {
+    type Input<'i> = Vec<u64>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, utils::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, utils::Error> {
+        Ok(first_invalid(
+            input.iter().copied().map(Ok),
+            self.preamble_len,
+        )?)
+    }
+
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, utils::Error> {
+        Ok(encryption_weakness(input, self.preamble_len)?)
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<u64>, Diagnostic> {
+    utils::parse_lines(input, parse_number)
+}
+
+fn parse_number(line: &str) -> Result<u64, Diagnostic> {
+    line.parse()
+        .map_err(|e| Diagnostic::new(line, line, Errors::from(e)))
+}
+
+/// First number that is not the sum of two of the `preamble_len` before it, only that window is
+/// kept and `cipher_text` is not consumed past the answer
+fn first_invalid<I>(cipher_text: I, preamble_len: usize) -> Result<u64, Errors>
+where
+    I: IntoIterator<Item = Result<u64, Errors>>,
+{
+    let mut window = VecDeque::with_capacity(preamble_len);
+    for number in cipher_text {
+        let number = number?;
+        if window.len() == preamble_len {
+            let valid = window
                 .iter()
-                .combinations(2)
-                .any(|c| c[0] + c[1] == cipher_text[i])
-                .not()
-                .then(|| cipher_text[i])
-        })
-        .ok_or(Errors::NoSolutionFound)
+                .tuple_combinations()
+                .any(|(a, b): (&u64, &u64)| a.checked_add(*b) == Some(number));
+            if !valid {
+                return Ok(number);
+            }
+            window.pop_front();
+        }
+        window.push_back(number);
+    }
+
+    Err(Errors::NoSolutionFound)
 }
 
 pub fn challenge1(input: &str, preamble_len: usize) -> Result<u64, Errors> {
-    let cipher_text: Vec<u64> = input.lines().map(str::parse).try_collect()?;
-    first_invalid(&cipher_text, preamble_len)
+    first_invalid(parse(input)?.into_iter().map(Ok), preamble_len)
 }
 
 pub fn challenge2(input: &str, preamble_len: usize) -> Result<u64, Errors> {
-    let cipher_text: Vec<u64> = input.lines().map(str::parse).try_collect()?;
-    let target = first_invalid(&cipher_text, preamble_len)?;
+    encryption_weakness(&parse(input)?, preamble_len)
+}
+
+/// [`challenge1`] reading one number at a time, only the preamble window is kept
+pub fn challenge1_from_reader(reader: impl BufRead, preamble_len: usize) -> Result<u64, Errors> {
+    let cipher_text = utils::read_lines(reader, parse_number).map(|number| Ok(number?));
+    first_invalid(cipher_text, preamble_len)
+}
+
+/// [`challenge2`] reading one number at a time, every number is kept but not the text
+pub fn challenge2_from_reader(reader: impl BufRead, preamble_len: usize) -> Result<u64, Errors> {
+    let cipher_text: Vec<u64> =
+        utils::read_lines(reader, parse_number).collect::<Result<_, _>>()?;
+    encryption_weakness(&cipher_text, preamble_len)
+}
+
+fn encryption_weakness(cipher_text: &[u64], preamble_len: usize) -> Result<u64, Errors> {
+    let target = first_invalid(cipher_text.iter().copied().map(Ok), preamble_len)?;
 
     let mut min_ptr = 0;
     let mut max_ptr = 2;
 
     while max_ptr <= cipher_text.len() {
         let range = &cipher_text[min_ptr..max_ptr];
-        let sum = range.iter().sum::<u64>();
+        let sum = range
+            .iter()
+            .try_fold(0_u64, |sum, &value| sum.checked_add(value));
 
-        match sum.cmp(&target) {
+        // A sum too large for a u64 is also too large for the target
+        match sum.map_or(Ordering::Greater, |sum| sum.cmp(&target)) {
             Ordering::Equal => {
                 return range
                     .iter()
                     .minmax()
                     .into_option()
-                    .map(|(min, max)| min + max)
+                    .and_then(|(min, max)| min.checked_add(*max))
                     .ok_or(Errors::NoSolutionFound);
             }
             Ordering::Greater if max_ptr > min_ptr + 2 => min_ptr += 1,
@@ -86,12 +164,28 @@ mod tests {
     #[test]
     fn test_challenge1() -> Result<(), super::Errors> {
         assert_eq!(super::challenge1(INPUT, 5)?, 127);
+        assert_eq!(super::challenge1_from_reader(INPUT.as_bytes(), 5)?, 127);
         Ok(())
     }
 
     #[test]
     fn test_challenge2() -> Result<(), super::Errors> {
         assert_eq!(super::challenge2(INPUT, 5)?, 62);
+        assert_eq!(super::challenge2_from_reader(INPUT.as_bytes(), 5)?, 62);
         Ok(())
     }
+
+    proptest::proptest! {
+        #[test]
+        fn test_reference((input, preamble_len) in generators::day9::cipher_text()) {
+            proptest::prop_assert_eq!(
+                super::challenge1(&input, preamble_len).ok(),
+                super::reference::challenge1(&input, preamble_len).ok()
+            );
+            proptest::prop_assert_eq!(
+                super::challenge2(&input, preamble_len).ok(),
+                super::reference::challenge2(&input, preamble_len).ok()
+            );
+        }
+    }
 }
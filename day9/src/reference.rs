@@ -0,0 +1,44 @@
+//! Brute force solvers, slow but simple enough to check the fast ones against
+
+use crate::{parse, Errors};
+
+/// [`challenge1`](crate::challenge1) trying every pair of the preamble of every number
+pub fn challenge1(input: &str, preamble_len: usize) -> Result<u64, Errors> {
+    first_invalid(&parse(input)?, preamble_len)
+}
+
+/// [`challenge2`](crate::challenge2) summing every contiguous range from scratch, by increasing
+/// end then increasing start
+pub fn challenge2(input: &str, preamble_len: usize) -> Result<u64, Errors> {
+    let cipher_text = parse(input)?;
+    let target = u128::from(first_invalid(&cipher_text, preamble_len)?);
+
+    for end in 2..=cipher_text.len() {
+        for start in 0..end - 1 {
+            let range = &cipher_text[start..end];
+            if range.iter().map(|&n| u128::from(n)).sum::<u128>() == target {
+                let min = range.iter().min().ok_or(Errors::NoSolutionFound)?;
+                let max = range.iter().max().ok_or(Errors::NoSolutionFound)?;
+                return min.checked_add(*max).ok_or(Errors::NoSolutionFound);
+            }
+        }
+    }
+
+    Err(Errors::NoSolutionFound)
+}
+
+fn first_invalid(cipher_text: &[u64], preamble_len: usize) -> Result<u64, Errors> {
+    for index in preamble_len..cipher_text.len() {
+        let preamble = &cipher_text[index - preamble_len..index];
+        let number = u128::from(cipher_text[index]);
+        let valid = (0..preamble.len()).any(|a| {
+            (a + 1..preamble.len())
+                .any(|b| u128::from(preamble[a]) + u128::from(preamble[b]) == number)
+        });
+        if !valid {
+            return Ok(cipher_text[index]);
+        }
+    }
+
+    Err(Errors::NoSolutionFound)
+}
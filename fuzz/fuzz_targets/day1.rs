@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use utils::Solution as _;
+
+fuzz_target!(|input: &str| {
+    let day = day1::Day1;
+    if let Ok(input) = day.parse(input) {
+        let _ = day.part1(&input);
+        let _ = day.part2(&input);
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use utils::Solution as _;
+
+fuzz_target!(|input: &str| {
+    let day = day14::Day14;
+    if let Ok(program) = day.parse(input) {
+        let _ = day.part1(&program);
+        let _ = day.part2(&program);
+    }
+});
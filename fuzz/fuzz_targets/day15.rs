@@ -0,0 +1,15 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use utils::Solution as _;
+
+fuzz_target!(|input: &str| {
+    // The turns bound the work of the game, enough of them to revisit numbers many times
+    let day = day15::Day15 {
+        turn2: 100_000,
+        ..day15::Day15::default()
+    };
+    if let Ok(input) = day.parse(input) {
+        let _ = day.part1(&input);
+        let _ = day.part2(&input);
+    }
+});
@@ -0,0 +1,63 @@
+use proptest::prelude::*;
+use std::collections::HashSet;
+
+use crate::Planted;
+
+const TARGET: i64 = 2020;
+
+/// Expense reports with exactly one pair and one triple of entries summing to 2020
+///
+/// The other entries are all above 2020 so they can not be part of a sum, and the planted
+/// entries are distinct and are filtered so that they do not form any other pair or triple,
+/// using an entry twice included.
+pub fn expense_report() -> impl Strategy<Value = Planted<i64>> {
+    let pair = (1..TARGET).prop_map(|a| (a, TARGET - a));
+    let triple = (1..TARGET - 1)
+        .prop_flat_map(|a| (Just(a), 1..TARGET - a))
+        .prop_map(|(a, b)| (a, b, TARGET - a - b));
+    let filler = prop::collection::vec(TARGET + 1..100_000, 0..200);
+
+    (pair, triple, filler)
+        .prop_filter(
+            "Planted entries must be the only sums",
+            |&(pair, triple, _)| only_sums(pair, triple),
+        )
+        .prop_flat_map(|((a, b), (x, y, z), filler)| {
+            let mut entries = vec![a, b, x, y, z];
+            entries.extend(filler);
+
+            (Just(a * b), Just(x * y * z), Just(entries).prop_shuffle())
+        })
+        .prop_map(|(part1, part2, entries)| Planted {
+            input: crate::lines(entries),
+            part1,
+            part2,
+        })
+}
+
+fn only_sums(pair: (i64, i64), triple: (i64, i64, i64)) -> bool {
+    let (x, y, z) = triple;
+    let entries = [pair.0, pair.1, x, y, z];
+    let set: HashSet<i64> = entries.iter().copied().collect();
+    if set.len() != entries.len() {
+        return false;
+    }
+
+    let is_pair = |a, b| (a, b) == pair || (b, a) == pair;
+    let is_triple = |a, b, c| {
+        let mut sorted = [a, b, c];
+        let mut planted = [x, y, z];
+        sorted.sort_unstable();
+        planted.sort_unstable();
+        sorted == planted
+    };
+
+    entries.iter().all(|&a| {
+        let pair_ok = !set.contains(&(TARGET - a)) || is_pair(a, TARGET - a);
+        let triple_ok = entries.iter().all(|&b| {
+            let c = TARGET - a - b;
+            !set.contains(&c) || is_triple(a, b, c)
+        });
+        pair_ok && triple_ok
+    })
+}
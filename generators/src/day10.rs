@@ -0,0 +1,69 @@
+use proptest::prelude::*;
+
+use crate::Planted;
+
+/// Adapter bags whose joltages differ by 1 or 3, starting from the outlet at 0
+///
+/// The device adds a final difference of 3. Only runs of consecutive differences of 1 can be
+/// rearranged, a run of `n` of them can be in `tribonacci(n)` ways so the number of arrangements
+/// is the product over the runs. Bags are kept under 60 adapters so that it fits in a `u64`.
+pub fn adapters() -> impl Strategy<Value = Planted<u64>> {
+    prop::collection::vec(prop_oneof![Just(1_u64), Just(3_u64)], 1..60)
+        .prop_flat_map(|differences| {
+            let joltages: Vec<u64> = differences
+                .iter()
+                .scan(0, |joltage, difference| {
+                    *joltage += difference;
+                    Some(*joltage)
+                })
+                .collect();
+            (Just(differences), Just(joltages).prop_shuffle())
+        })
+        .prop_map(|(differences, joltages)| {
+            let ones = differences.iter().filter(|&&d| d == 1).count() as u64;
+            let threes = differences.len() as u64 - ones + 1;
+            let arrangements = differences
+                .split(|&difference| difference == 3)
+                .map(|run| tribonacci(run.len()))
+                .product();
+
+            Planted {
+                input: crate::lines(joltages),
+                part1: ones * threes,
+                part2: arrangements,
+            }
+        })
+}
+
+/// Ways to cover a run of `n` differences of 1 with steps of at most 3
+fn tribonacci(n: usize) -> u64 {
+    let (mut a, mut b, mut c) = (0, 0, 1);
+    for _ in 0..n {
+        let next = a + b + c;
+        a = b;
+        b = c;
+        c = next;
+    }
+    c
+}
+
+/// Bags of adapters with differences of 1 to 3 jolts in any order, and the odd gap of 4 that no
+/// adapter can bridge
+///
+/// No answer is planted. The bags are small enough to count every arrangement one at a time.
+pub fn adapter_bags() -> impl Strategy<Value = String> {
+    let difference = prop_oneof![8 => 1_u64..=3, 1 => Just(4_u64)];
+
+    prop::collection::vec(difference, 1..20)
+        .prop_flat_map(|differences| {
+            let joltages: Vec<u64> = differences
+                .iter()
+                .scan(0, |joltage, difference| {
+                    *joltage += difference;
+                    Some(*joltage)
+                })
+                .collect();
+            Just(joltages).prop_shuffle()
+        })
+        .prop_map(crate::lines)
+}
@@ -0,0 +1,82 @@
+use proptest::prelude::*;
+
+use crate::Planted;
+
+const PRIMES: [u64; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+/// Bus notes built around a planted earliest departure and a planted alignment time
+///
+/// The bus ids are distinct primes so the alignment is unique below their product. Each bus is
+/// placed at an offset congruent to minus the alignment modulo its id, the departure time is
+/// chosen so that no bus leaves exactly then and a single bus is the first to leave after it.
+pub fn bus_notes() -> impl Strategy<Value = Planted<u64, i64>> {
+    prop::sample::subsequence(PRIMES.to_vec(), 1..=9)
+        .prop_flat_map(|ids| {
+            let product: u64 = ids.iter().product();
+            (Just(ids), 0..product, 1_u64..1_000_000)
+        })
+        .prop_filter("A single bus must leave first", |(ids, _, departure)| {
+            first_bus(ids, *departure).is_some()
+        })
+        .prop_map(|(ids, alignment, departure)| {
+            let mut slots: Vec<Option<u64>> = Vec::new();
+            for &id in &ids {
+                let mut offset = (id - alignment % id) % id;
+                while slots.get(offset as usize).copied().flatten().is_some() {
+                    offset += id;
+                }
+                let offset = offset as usize;
+                if slots.len() <= offset {
+                    slots.resize(offset + 1, None);
+                }
+                slots[offset] = Some(id);
+            }
+
+            let bus_ids = slots
+                .iter()
+                .map(|slot| slot.map_or_else(|| "x".to_string(), |id| id.to_string()))
+                .collect::<Vec<_>>()
+                .join(",");
+            let (id, wait) = first_bus(&ids, departure).unwrap_or((0, 0));
+
+            Planted {
+                input: format!("{}\n{}", departure, bus_ids),
+                part1: id * wait,
+                part2: alignment as i64,
+            }
+        })
+}
+
+/// Id and wait of the first bus to leave after `departure`, `None` if a bus leaves right at
+/// `departure` or if several leave first together
+fn first_bus(ids: &[u64], departure: u64) -> Option<(u64, u64)> {
+    if ids.iter().any(|&id| departure.is_multiple_of(id)) {
+        return None;
+    }
+
+    let mut waits: Vec<(u64, u64)> = ids.iter().map(|&id| (id - departure % id, id)).collect();
+    waits.sort_unstable();
+    match waits.as_slice() {
+        [(wait, id), (next, _), ..] if wait != next => Some((*id, *wait)),
+        [(wait, id)] => Some((*id, *wait)),
+        _ => None,
+    }
+}
+
+/// Bus notes with a few small ids that may share factors, so that some never align
+///
+/// No answer is planted. The product of the ids stays small enough to try every time below it.
+pub fn small_schedules() -> impl Strategy<Value = String> {
+    let bus = (0_usize..3, 1_u64..=16);
+    (0_u64..1000, prop::collection::vec(bus, 1..=5)).prop_map(|(departure, buses)| {
+        let bus_ids: Vec<String> = buses
+            .iter()
+            .flat_map(|&(out_of_service, id)| {
+                std::iter::repeat_n("x".to_string(), out_of_service).chain(Some(id.to_string()))
+            })
+            .collect();
+        format!("{}\n{}", departure, bus_ids.join(","))
+    })
+}
@@ -0,0 +1,82 @@
+use proptest::{prelude::*, sample::Index};
+
+/// Ticket notes with a handful of fields and a planted assignment of the fields to the ticket
+/// positions, every other field name starts with `departure`
+///
+/// Field `k` accepts `0` to `10k + 9`, so a value between `10j` and `10j + 9` rules out the fields
+/// before `j`. The position of field `k` sees values up to that block, one ticket reaching it, so
+/// it is left with fields `k` and after: the last field is settled first and each settled field
+/// leaves a single one to the position before. Elimination alone finds the assignment, and it is
+/// the only one. Some nearby tickets have a value no field accepts and must be ignored.
+pub fn ticket_notes() -> impl Strategy<Value = String> {
+    (1_usize..=6)
+        .prop_flat_map(|fields| {
+            let order = Just((0..fields).collect::<Vec<usize>>()).prop_shuffle();
+            let ticket = prop::collection::vec((any::<Index>(), 0_u64..10), fields);
+            (
+                order,
+                prop::collection::vec(0_u64..1000, fields),
+                ticket.clone(),
+                prop::collection::vec((ticket, prop::option::of(any::<Index>())), 0..15),
+                any::<Index>(),
+            )
+        })
+        .prop_map(|(order, mine, witness, nearby, at)| {
+            let fields = order.len();
+            let rules = (0..fields).map(|field| {
+                let name = if field % 2 == 0 {
+                    "departure"
+                } else {
+                    "arrival"
+                };
+                let top = 10 * field as u64 + 9;
+                let single = 900 + field as u64;
+                format!("{} {}: 0-{} or {}-{}", name, field, top, single, single)
+            });
+
+            // The position of field `k` gets a value of a block up to `k`, the witness reaches it
+            let values = |ticket: &[(Index, u64)], witness: bool| -> Vec<u64> {
+                ticket
+                    .iter()
+                    .zip(&order)
+                    .map(|(&(block, offset), &field)| {
+                        let block = if witness {
+                            field
+                        } else {
+                            block.index(field + 1)
+                        };
+                        10 * block as u64 + offset
+                    })
+                    .collect()
+            };
+            let mut tickets: Vec<Vec<u64>> = nearby
+                .iter()
+                .map(|(ticket, invalid)| {
+                    let mut values = values(ticket, false);
+                    if let Some(position) = invalid {
+                        values[position.index(fields)] = 500;
+                    }
+                    values
+                })
+                .collect();
+            tickets.insert(at.index(tickets.len() + 1), values(&witness, true));
+
+            let nearby = std::iter::once("nearby tickets:".to_string())
+                .chain(tickets.iter().map(|values| ticket(values)));
+            format!(
+                "{}\n\nyour ticket:\n{}\n\n{}",
+                crate::lines(rules),
+                ticket(&mine),
+                crate::lines(nearby)
+            )
+        })
+}
+
+/// Comma separated ticket values
+fn ticket(values: &[u64]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
@@ -0,0 +1,56 @@
+use proptest::{prelude::*, sample::Index};
+
+/// Rules and short messages of `a` and `b`, with rules 0, 8 and 11 laid out like the puzzle's so
+/// that the second part can loop them
+///
+/// No answer is planted. Rules 42 and 31 are built from helper rules that only refer to the
+/// helpers after them, the last two being the literals, so no rule refers back to itself.
+/// Messages are short enough that a fair share of them match.
+pub fn messages() -> impl Strategy<Value = String> {
+    let alternatives =
+        || prop::collection::vec(prop::collection::vec(any::<Index>(), 1..=2), 1..=2);
+
+    (0_usize..=4)
+        .prop_flat_map(move |compound| {
+            (
+                prop::collection::vec(alternatives(), compound),
+                alternatives(),
+                alternatives(),
+                prop::collection::vec("[ab]{1,12}", 1..10),
+            )
+        })
+        .prop_map(|(compound, rule42, rule31, messages)| {
+            let helpers = compound.len() + 2;
+            // References are picked among the helpers from `after` on
+            let rule = |alternatives: &[Vec<Index>], after: usize| {
+                alternatives
+                    .iter()
+                    .map(|references| {
+                        references
+                            .iter()
+                            .map(|reference| {
+                                (100 + after + reference.index(helpers - after)).to_string()
+                            })
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            };
+
+            let mut rules = vec![
+                "0: 8 11".to_string(),
+                "8: 42".to_string(),
+                "11: 42 31".to_string(),
+                format!("42: {}", rule(&rule42, 0)),
+                format!("31: {}", rule(&rule31, 0)),
+            ];
+            rules.extend(compound.iter().enumerate().map(|(index, alternatives)| {
+                format!("{}: {}", 100 + index, rule(alternatives, index + 1))
+            }));
+            rules.push(format!("{}: \"a\"", 100 + helpers - 2));
+            rules.push(format!("{}: \"b\"", 100 + helpers - 1));
+
+            format!("{}\n\n{}", crate::lines(rules), crate::lines(messages))
+        })
+}
@@ -0,0 +1,41 @@
+use proptest::prelude::*;
+
+use crate::Planted;
+
+/// Boarding passes for a contiguous block of seats with exactly one gap, the missing seat
+///
+/// The block has at least one seat on each side of the gap, the first part's answer is the last
+/// seat of the block.
+pub fn boarding_passes() -> impl Strategy<Value = Planted<u16>> {
+    (1_u16..1023)
+        .prop_flat_map(|gap| (0..gap, gap + 1..1024, Just(gap)))
+        .prop_flat_map(|(first, last, gap)| {
+            let seats: Vec<u16> = (first..=last).filter(|&seat| seat != gap).collect();
+            (Just(last), Just(gap), Just(seats).prop_shuffle())
+        })
+        .prop_map(|(last, gap, seats)| Planted {
+            input: crate::lines(seats.into_iter().map(boarding_pass)),
+            part1: last,
+            part2: gap,
+        })
+}
+
+/// Binary space partitioned encoding of a seat id, 7 `F`/`B` for the row then 3 `L`/`R`
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(generators::day5::boarding_pass(357), "FBFBBFFRLR");
+/// assert_eq!(generators::day5::boarding_pass(820), "BBFFBBFRLL");
+/// ```
+pub fn boarding_pass(seat: u16) -> String {
+    (0..10)
+        .rev()
+        .map(|bit| match (bit >= 3, seat >> bit & 1 == 1) {
+            (true, false) => 'F',
+            (true, true) => 'B',
+            (false, false) => 'L',
+            (false, true) => 'R',
+        })
+        .collect()
+}
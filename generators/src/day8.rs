@@ -0,0 +1,40 @@
+use proptest::prelude::*;
+
+use crate::Planted;
+
+/// Boot code that loops forever because a single `nop` was corrupted into a `jmp`
+///
+/// Without the corruption the program runs straight through: every other instruction is an
+/// `acc` or a `nop +0`, and a `nop +0` turned into a `jmp +0` loops on itself, so repairing the
+/// corrupted `jmp` is the only way to terminate. The corrupted `jmp` goes back to an earlier
+/// instruction, or to itself.
+pub fn corrupted_program() -> impl Strategy<Value = Planted<isize>> {
+    let instruction = prop_oneof![(-100_isize..=100).prop_map(Some), Just(None)];
+
+    prop::collection::vec(instruction, 1..300)
+        .prop_flat_map(|program| {
+            let len = program.len();
+            (Just(program), 0..len)
+        })
+        .prop_flat_map(|(program, corrupted)| (Just(program), Just(corrupted), 0..=corrupted))
+        .prop_map(|(program, corrupted, back)| {
+            let accumulated =
+                |instructions: &[Option<isize>]| -> isize { instructions.iter().flatten().sum() };
+            let part1 = accumulated(&program[..corrupted]);
+            let part2 = part1 + accumulated(&program[corrupted + 1..]);
+
+            let lines = program.iter().enumerate().map(|(index, instruction)| {
+                match (index == corrupted, instruction) {
+                    (true, _) => format!("jmp -{}", back),
+                    (false, Some(argument)) => format!("acc {:+}", argument),
+                    (false, None) => "nop +0".to_string(),
+                }
+            });
+
+            Planted {
+                input: crate::lines(lines),
+                part1,
+                part2,
+            }
+        })
+}
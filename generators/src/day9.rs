@@ -0,0 +1,12 @@
+use proptest::prelude::*;
+
+/// XMAS cipher texts of small positive numbers along with their preamble length
+///
+/// No answer is planted. The preamble is short and the numbers small so that invalid numbers,
+/// and contiguous ranges summing to them, are common.
+pub fn cipher_text() -> impl Strategy<Value = (String, usize)> {
+    (2_usize..=5).prop_flat_map(|preamble_len| {
+        let numbers = prop::collection::vec(1_u64..=60, preamble_len..preamble_len + 40);
+        (numbers.prop_map(crate::lines), Just(preamble_len))
+    })
+}
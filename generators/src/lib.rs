@@ -0,0 +1,33 @@
+//! Random puzzle inputs with a known answer, as [`proptest`] strategies
+//!
+//! Each module builds valid inputs for one day around answers planted during generation, so that
+//! the solvers can be checked on inputs much larger and more varied than the puzzle examples.
+//!
+//! The tests run proptest's default 256 cases, comparing against the brute force reference
+//! solvers included. Set `PROPTEST_CASES` to run more, for instance after changing a solver.
+
+pub mod day1;
+pub mod day10;
+pub mod day13;
+pub mod day16;
+pub mod day19;
+pub mod day5;
+pub mod day8;
+pub mod day9;
+
+/// Generated puzzle input along with the answers to both parts
+#[derive(Debug, Clone)]
+pub struct Planted<A, B = A> {
+    pub input: String,
+    pub part1: A,
+    pub part2: B,
+}
+
+/// One value per line, the way most puzzle inputs are laid out
+fn lines<T: ToString>(values: impl IntoIterator<Item = T>) -> String {
+    values
+        .into_iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
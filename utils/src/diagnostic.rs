@@ -0,0 +1,184 @@
+use crate::Error;
+use std::{fmt, iter::FromIterator, ops::Range};
+
+/// Parse error located in the input, with the 1-based line, the column span and the offending line
+///
+/// Displays as `Line 2, column 5: <cause>`, use [`Diagnostic::render`] for a compiler style report
+/// with a caret under the bad token.
+#[derive(Debug)]
+pub struct Diagnostic {
+    line: usize,
+    columns: Range<usize>,
+    snippet: String,
+    cause: Error,
+}
+
+impl Diagnostic {
+    /// Diagnostic pointing at `token` within `line`
+    ///
+    /// `token` should be a slice of `line`, otherwise the whole line is pointed at. The line number
+    /// defaults to 1, parsers that know where `line` came from set it with [`Diagnostic::on_line`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utils::Diagnostic;
+    ///
+    /// let line = "mem[8] = 1x";
+    /// let error = "1x".parse::<u64>().unwrap_err();
+    /// let diagnostic = Diagnostic::new(line, &line[9..], error).on_line(3);
+    ///
+    /// assert_eq!(diagnostic.line(), 3);
+    /// assert_eq!(diagnostic.columns(), 10..12);
+    /// assert_eq!(diagnostic.to_string(), "Line 3, column 10: invalid digit found in string");
+    /// ```
+    pub fn new(line: &str, token: &str, cause: impl Into<Error>) -> Self {
+        let start = (token.as_ptr() as usize)
+            .checked_sub(line.as_ptr() as usize)
+            .filter(|start| start + token.len() <= line.len());
+
+        let columns = match start {
+            Some(start) => {
+                let column = line[..start].chars().count() + 1;
+                column..column + token.chars().count().max(1)
+            }
+            None => 1..line.chars().count().max(1) + 1,
+        };
+
+        Self {
+            line: 1,
+            columns,
+            snippet: line.to_string(),
+            cause: cause.into(),
+        }
+    }
+
+    /// Diagnostic pointing just past the last line, for input that ends too early
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utils::Diagnostic;
+    ///
+    /// let diagnostic = Diagnostic::end_of_input("939\n", "Missing bus ids");
+    /// assert_eq!(diagnostic.to_string(), "Line 2, column 1: Missing bus ids");
+    /// ```
+    pub fn end_of_input(input: &str, cause: impl Into<Error>) -> Self {
+        Self::new("", "", cause).on_line(input.lines().count() + 1)
+    }
+
+    /// Set the 1-based line number the diagnostic points at
+    pub fn on_line(mut self, line: usize) -> Self {
+        self.line = line;
+        self
+    }
+
+    /// 1-based line number
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// 1-based columns of the offending token, counted in characters, end exclusive
+    pub fn columns(&self) -> Range<usize> {
+        self.columns.clone()
+    }
+
+    /// The whole line the diagnostic points at
+    pub fn snippet(&self) -> &str {
+        &self.snippet
+    }
+
+    /// The underlying error
+    pub fn cause(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        &*self.cause
+    }
+
+    /// Report the diagnostic like a compiler error, with a caret under the offending token
+    ///
+    /// `origin` names where the input came from, usually its path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utils::Diagnostic;
+    ///
+    /// let line = "F1O";
+    /// let error = "1O".parse::<u64>().unwrap_err();
+    /// let diagnostic = Diagnostic::new(line, &line[1..], error).on_line(12);
+    ///
+    /// assert_eq!(
+    ///     diagnostic.render("input.txt"),
+    ///     "error: invalid digit found in string
+    ///   --> input.txt:12:2
+    ///    |
+    /// 12 | F1O
+    ///    |  ^^
+    /// "
+    /// );
+    /// ```
+    pub fn render(&self, origin: &str) -> String {
+        let gutter = self.line.to_string().len();
+        let padding = " ".repeat(self.columns.start - 1);
+        let carets = "^".repeat(self.columns.len());
+
+        format!(
+            "error: {cause}\n\
+             {empty:gutter$}--> {origin}:{line}:{column}\n\
+             {empty:gutter$} |\n\
+             {line} | {snippet}\n\
+             {empty:gutter$} | {padding}{carets}\n",
+            cause = self.cause,
+            empty = "",
+            origin = origin,
+            gutter = gutter,
+            line = self.line,
+            column = self.columns.start,
+            snippet = self.snippet,
+            padding = padding,
+            carets = carets,
+        )
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Line {}, column {}: {}",
+            self.line, self.columns.start, self.cause
+        )
+    }
+}
+
+impl std::error::Error for Diagnostic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause.source()
+    }
+}
+
+/// Parse every line of the input, reporting the first failure with its line number
+///
+/// # Examples
+///
+/// ```
+/// use utils::Diagnostic;
+///
+/// let parse = |line: &str| line.parse::<u64>().map_err(|e| Diagnostic::new(line, line, e));
+///
+/// let numbers: Vec<u64> = utils::parse_lines("1\n2\n3", parse).unwrap();
+/// assert_eq!(numbers, vec![1, 2, 3]);
+///
+/// let error = utils::parse_lines::<_, _, Vec<u64>>("1\ntwo\n3", parse).unwrap_err();
+/// assert_eq!(error.line(), 2);
+/// ```
+pub fn parse_lines<'i, T, F, C>(input: &'i str, mut parse: F) -> Result<C, Diagnostic>
+where
+    F: FnMut(&'i str) -> Result<T, Diagnostic>,
+    C: FromIterator<T>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| parse(line).map_err(|e| e.on_line(index + 1)))
+        .collect()
+}
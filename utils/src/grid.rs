@@ -0,0 +1,330 @@
+use crate::Diagnostic;
+use displaydoc::Display;
+use std::{
+    convert::TryFrom,
+    fmt,
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+use thiserror::Error;
+
+/// Row and column offsets of the four orthogonal neighbors
+pub const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+/// Row and column offsets of the eight neighbors, orthogonal and diagonal
+pub const SURROUNDING: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+];
+
+/// Cause of a [`Diagnostic`] reported when parsing a grid
+#[derive(Debug, Error, Display)]
+pub enum GridError<E: std::error::Error + 'static> {
+    /// Row is {width} cells wide but the first row is {expected}
+    JaggedRow { width: usize, expected: usize },
+    /// {0}
+    InvalidCell(E),
+}
+
+/// Dense 2D map of cells addressed by `(row, column)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// Create a grid filled with copies of a cell
+    pub fn new(height: usize, width: usize, cell: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: vec![cell; width * height],
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Cell at the position, `None` if it is outside of the grid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utils::Grid;
+    ///
+    /// let grid: Grid<char> = "ab\ncd".parse().unwrap();
+    /// assert_eq!(grid.get(1, 0), Some(&'c'));
+    /// assert_eq!(grid.get(0, 2), None);
+    /// ```
+    pub fn get(&self, row: usize, column: usize) -> Option<&T> {
+        if row < self.height && column < self.width {
+            self.cells.get(row * self.width + column)
+        } else {
+            None
+        }
+    }
+
+    /// Mutable cell at the position, `None` if it is outside of the grid
+    pub fn get_mut(&mut self, row: usize, column: usize) -> Option<&mut T> {
+        if row < self.height && column < self.width {
+            self.cells.get_mut(row * self.width + column)
+        } else {
+            None
+        }
+    }
+
+    /// Cell at the position with both axis wrapping around as if the grid repeated forever,
+    /// `None` only if the grid is empty
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utils::Grid;
+    ///
+    /// let grid: Grid<char> = "ab\ncd".parse().unwrap();
+    /// assert_eq!(grid.get_wrapping(3, 4), Some(&'c'));
+    /// assert_eq!(grid.get_wrapping(-1, -1), Some(&'d'));
+    /// ```
+    pub fn get_wrapping(&self, row: isize, column: isize) -> Option<&T> {
+        if self.cells.is_empty() {
+            return None;
+        }
+
+        let row = row.rem_euclid(self.height as isize) as usize;
+        let column = column.rem_euclid(self.width as isize) as usize;
+        self.get(row, column)
+    }
+
+    /// Position one step away in a direction, `None` if it is outside of the grid
+    pub fn step(
+        &self,
+        (row, column): (usize, usize),
+        direction: (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let row = checked_offset(row, direction.0)?;
+        let column = checked_offset(column, direction.1)?;
+
+        (row < self.height && column < self.width).then_some((row, column))
+    }
+
+    /// Cells and positions of the orthogonal neighbors within the grid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utils::Grid;
+    ///
+    /// let grid: Grid<char> = "abc\ndef\nghi".parse().unwrap();
+    /// let corner: String = grid.neighbors4((0, 0)).map(|(_, &c)| c).collect();
+    /// assert_eq!(corner, "bd");
+    /// ```
+    pub fn neighbors4(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.neighbors(position, &ORTHOGONAL)
+    }
+
+    /// Cells and positions of the orthogonal and diagonal neighbors within the grid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utils::Grid;
+    ///
+    /// let grid: Grid<char> = "abc\ndef\nghi".parse().unwrap();
+    /// assert_eq!(grid.neighbors8((1, 1)).count(), 8);
+    /// let corner: String = grid.neighbors8((2, 2)).map(|(_, &c)| c).collect();
+    /// assert_eq!(corner, "efh");
+    /// ```
+    pub fn neighbors8(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.neighbors(position, &SURROUNDING)
+    }
+
+    fn neighbors(
+        &self,
+        position: (usize, usize),
+        directions: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        directions.iter().filter_map(move |&direction| {
+            let next = self.step(position, direction)?;
+            Some((next, &self[next]))
+        })
+    }
+
+    /// Cells and positions met moving in a direction from a position, excluding the starting
+    /// position, until the edge of the grid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utils::Grid;
+    ///
+    /// let grid: Grid<char> = "abc\ndef\nghi".parse().unwrap();
+    /// let diagonal: String = grid.ray((0, 0), (1, 1)).map(|(_, &c)| c).collect();
+    /// assert_eq!(diagonal, "ei");
+    /// ```
+    pub fn ray(
+        &self,
+        position: (usize, usize),
+        direction: (isize, isize),
+    ) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        let mut current = position;
+        std::iter::from_fn(move || {
+            current = self.step(current, direction)?;
+            Some((current, &self[current]))
+        })
+    }
+
+    /// Cells along with their position, row by row
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(index, cell)| ((index / width, index % width), cell))
+    }
+
+    /// Rows of the grid from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        // `chunks` panics on 0 so an empty grid gets a width of 1, it has no cells to chunk anyway
+        self.cells.chunks(self.width.max(1))
+    }
+}
+
+/// Add a signed offset to a position component, `None` if the result would be negative
+fn checked_offset(value: usize, offset: isize) -> Option<usize> {
+    if offset.is_negative() {
+        value.checked_sub(offset.unsigned_abs())
+    } else {
+        value.checked_add(offset as usize)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, column): (usize, usize)) -> &Self::Output {
+        assert!(
+            row < self.height && column < self.width,
+            "position ({}, {}) is outside of the {}x{} grid",
+            row,
+            column,
+            self.height,
+            self.width
+        );
+        &self.cells[row * self.width + column]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, column): (usize, usize)) -> &mut Self::Output {
+        assert!(
+            row < self.height && column < self.width,
+            "position ({}, {}) is outside of the {}x{} grid",
+            row,
+            column,
+            self.height,
+            self.width
+        );
+        &mut self.cells[row * self.width + column]
+    }
+}
+
+/// Parse one cell per character, one row per line
+///
+/// # Examples
+///
+/// ```
+/// use utils::Grid;
+///
+/// let grid: Grid<char> = "ab\ncd\n".parse().unwrap();
+/// assert_eq!((grid.height(), grid.width()), (2, 2));
+///
+/// let jagged = "ab\nc".parse::<Grid<char>>().unwrap_err();
+/// assert_eq!(jagged.line(), 2);
+/// assert_eq!(jagged.to_string(), "Line 2, column 1: Row is 1 cells wide but the first row is 2");
+/// ```
+impl<T> FromStr for Grid<T>
+where
+    T: TryFrom<char>,
+    T::Error: std::error::Error + Send + Sync + 'static,
+{
+    type Err = Diagnostic;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut cells = Vec::new();
+        let mut width = 0;
+        let mut height = 0;
+
+        for (row, line) in input.lines().enumerate() {
+            let mut line_width = 0;
+            for (offset, c) in line.char_indices() {
+                let cell = T::try_from(c).map_err(|source| {
+                    let token = &line[offset..offset + c.len_utf8()];
+                    Diagnostic::new(line, token, GridError::InvalidCell(source)).on_line(row + 1)
+                })?;
+                cells.push(cell);
+                line_width += 1;
+            }
+
+            if row == 0 {
+                width = line_width;
+            } else if line_width != width {
+                let error = GridError::<T::Error>::JaggedRow {
+                    width: line_width,
+                    expected: width,
+                };
+                return Err(Diagnostic::new(line, line, error).on_line(row + 1));
+            }
+            height += 1;
+        }
+
+        Ok(Self {
+            cells,
+            width,
+            height,
+        })
+    }
+}
+
+/// Print the grid back as text, one line per row
+///
+/// # Examples
+///
+/// ```
+/// use utils::Grid;
+///
+/// let grid: Grid<char> = "ab\ncd".parse().unwrap();
+/// assert_eq!(grid.to_string(), "ab\ncd\n");
+/// ```
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,16 @@
+mod diagnostic;
+pub mod grid;
+pub mod modular;
+mod read;
+mod records;
+mod solution;
+
+pub use diagnostic::{parse_lines, Diagnostic};
+pub use grid::{Grid, GridError};
+pub use read::{read_lines, ReadError, ReadLines};
+pub use records::{records, Record, Records};
+pub use solution::{Error, Solution};
+
 /// Helper trait for converting from using unstable feature "bool_to_option" on stable
 pub trait BoolExt: Sized {
     /// Returns `Some(t)` if the `bool` is `true`, or `None` otherwise.
@@ -0,0 +1,168 @@
+//! Modular arithmetic on `i128`, every operation is checked so overflow is reported instead of
+//! wrapping or panicking.
+
+use displaydoc::Display;
+use thiserror::Error;
+
+#[derive(Debug, Error, Display, Clone, Copy, PartialEq, Eq)]
+pub enum ModularError {
+    /// Modulus {0} is not positive
+    InvalidModulus(i128),
+    /// The congruences contradict each other
+    NoSolution,
+    /// Intermediate result does not fit in 128 bits
+    Overflow,
+}
+
+/// Extended Euclidean algorithm
+///
+/// Returns `(g, x, y)` where `g` is the non-negative greatest common divisor of `a` and `b` and
+/// `a * x + b * y == g`, or `None` if a step overflows, which only happens around `i128::MIN`.
+///
+/// # Examples
+///
+/// ```
+/// use utils::modular::extended_gcd;
+///
+/// assert_eq!(extended_gcd(240, 46), Some((2, -9, 47)));
+/// assert_eq!(extended_gcd(-4, 6), Some((2, 1, 1)));
+/// assert_eq!(extended_gcd(0, 0), Some((0, 1, 0)));
+/// assert_eq!(extended_gcd(i128::MIN, 0), None);
+/// ```
+pub fn extended_gcd(a: i128, b: i128) -> Option<(i128, i128, i128)> {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1_i128, 0_i128);
+    let (mut old_y, mut y) = (0_i128, 1_i128);
+
+    while r != 0 {
+        let quotient = old_r.checked_div(r)?;
+        (old_r, r) = (r, old_r.checked_sub(quotient.checked_mul(r)?)?);
+        (old_x, x) = (x, old_x.checked_sub(quotient.checked_mul(x)?)?);
+        (old_y, y) = (y, old_y.checked_sub(quotient.checked_mul(y)?)?);
+    }
+
+    if old_r < 0 {
+        Some((
+            old_r.checked_neg()?,
+            old_x.checked_neg()?,
+            old_y.checked_neg()?,
+        ))
+    } else {
+        Some((old_r, old_x, old_y))
+    }
+}
+
+/// Multiplicative inverse of `a` modulo `modulus`, in `0..modulus`
+///
+/// `None` if `a` and `modulus` are not coprime or `modulus` is not positive.
+///
+/// # Examples
+///
+/// ```
+/// use utils::modular::mod_inverse;
+///
+/// assert_eq!(mod_inverse(3, 11), Some(4));
+/// assert_eq!(mod_inverse(-3, 11), Some(7));
+/// assert_eq!(mod_inverse(4, 6), None);
+/// assert_eq!(mod_inverse(3, 0), None);
+/// ```
+pub fn mod_inverse(a: i128, modulus: i128) -> Option<i128> {
+    if modulus <= 0 {
+        return None;
+    }
+
+    let (gcd, x, _) = extended_gcd(a.rem_euclid(modulus), modulus)?;
+    (gcd == 1).then(|| x.rem_euclid(modulus))
+}
+
+/// `a * b` modulo `modulus`, in `0..modulus`, without overflowing even for moduli close to
+/// `i128::MAX`
+///
+/// `None` if `modulus` is not positive.
+///
+/// # Examples
+///
+/// ```
+/// use utils::modular::mul_mod;
+///
+/// assert_eq!(mul_mod(7, -3, 5), Some(4));
+/// assert_eq!(mul_mod(i128::MAX - 1, 2, i128::MAX), Some(i128::MAX - 2));
+/// ```
+pub fn mul_mod(a: i128, b: i128, modulus: i128) -> Option<i128> {
+    if modulus <= 0 {
+        return None;
+    }
+
+    let (mut a, mut b) = (a.rem_euclid(modulus), b.rem_euclid(modulus));
+    if let Some(product) = a.checked_mul(b) {
+        return Some(product % modulus);
+    }
+
+    // Double and add, `add` never leaves `0..modulus` so it cannot overflow
+    let add = |x: i128, y: i128| {
+        if x >= modulus - y {
+            x - (modulus - y)
+        } else {
+            x + y
+        }
+    };
+    let mut product = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            product = add(product, a);
+        }
+        a = add(a, a);
+        b >>= 1;
+    }
+
+    Some(product)
+}
+
+/// Solve a system of congruences `x ≡ remainder (mod modulus)` with the Chinese remainder theorem
+///
+/// The moduli do not need to be pairwise coprime. Returns `(x, lcm)` where `lcm` is the least
+/// common multiple of the moduli and `x` in `0..lcm` is the smallest solution, every other
+/// solution being `x` plus a multiple of `lcm`.
+///
+/// # Examples
+///
+/// ```
+/// use utils::modular::{chinese_remainder, ModularError};
+///
+/// assert_eq!(chinese_remainder(vec![(2, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+/// assert_eq!(chinese_remainder(vec![(0, 4), (4, 6)]), Ok((4, 12)));
+/// assert_eq!(chinese_remainder(vec![(0, 4), (5, 6)]), Err(ModularError::NoSolution));
+/// assert_eq!(chinese_remainder(vec![(1, 0)]), Err(ModularError::InvalidModulus(0)));
+/// assert_eq!(chinese_remainder(vec![]), Ok((0, 1)));
+/// ```
+pub fn chinese_remainder<I>(congruences: I) -> Result<(i128, i128), ModularError>
+where
+    I: IntoIterator<Item = (i128, i128)>,
+{
+    congruences
+        .into_iter()
+        .try_fold((0, 1), |(x, lcm), (remainder, modulus)| {
+            if modulus <= 0 {
+                return Err(ModularError::InvalidModulus(modulus));
+            }
+            let remainder = remainder.rem_euclid(modulus);
+
+            // `x + lcm * t ≡ remainder (mod modulus)` has a solution only if the gcd divides
+            // the difference, then `t ≡ difference / gcd * inverse (mod modulus / gcd)`
+            let (gcd, inverse, _) = extended_gcd(lcm, modulus).ok_or(ModularError::Overflow)?;
+            let difference = remainder.checked_sub(x).ok_or(ModularError::Overflow)?;
+            if difference % gcd != 0 {
+                return Err(ModularError::NoSolution);
+            }
+
+            let step = modulus / gcd;
+            let t = mul_mod(difference / gcd, inverse, step).ok_or(ModularError::Overflow)?;
+            let x = lcm
+                .checked_mul(t)
+                .and_then(|offset| offset.checked_add(x))
+                .ok_or(ModularError::Overflow)?;
+            let lcm = lcm.checked_mul(step).ok_or(ModularError::Overflow)?;
+
+            Ok((x, lcm))
+        })
+}
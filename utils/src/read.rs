@@ -0,0 +1,79 @@
+use crate::Diagnostic;
+use displaydoc::Display;
+use std::io::{self, BufRead};
+use thiserror::Error;
+
+/// Failure while parsing input streamed from a reader
+#[derive(Debug, Error, Display)]
+pub enum ReadError {
+    /// Could not read the input: {0}
+    Io(#[from] io::Error),
+    /// Invalid input: {0}
+    Invalid(#[from] Diagnostic),
+}
+
+/// Parse every line of a reader as it is read, without holding more than one line in memory
+///
+/// Lines are yielded without their `\n` or `\r\n` terminator, and parse failures get the line
+/// number they occurred on. `parse` only sees each line while it is in the buffer, so it must
+/// return owned values.
+///
+/// # Examples
+///
+/// ```
+/// use utils::{Diagnostic, ReadError};
+///
+/// let parse = |line: &str| line.parse::<u64>().map_err(|e| Diagnostic::new(line, line, e));
+///
+/// let numbers = utils::read_lines("1\r\n2\n3".as_bytes(), parse);
+/// assert_eq!(numbers.collect::<Result<Vec<_>, _>>().unwrap(), vec![1, 2, 3]);
+///
+/// let error = utils::read_lines("1\ntwo\n3".as_bytes(), parse)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap_err();
+/// assert!(matches!(error, ReadError::Invalid(diagnostic) if diagnostic.line() == 2));
+/// ```
+pub fn read_lines<R, T, F>(reader: R, parse: F) -> ReadLines<R, F>
+where
+    R: BufRead,
+    F: FnMut(&str) -> Result<T, Diagnostic>,
+{
+    ReadLines {
+        reader,
+        parse,
+        buffer: String::new(),
+        line: 0,
+    }
+}
+
+/// Iterator over the parsed lines of a reader, see [`read_lines`]
+pub struct ReadLines<R, F> {
+    reader: R,
+    parse: F,
+    buffer: String,
+    line: usize,
+}
+
+impl<R, T, F> Iterator for ReadLines<R, F>
+where
+    R: BufRead,
+    F: FnMut(&str) -> Result<T, Diagnostic>,
+{
+    type Item = Result<T, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buffer.clear();
+        match self.reader.read_line(&mut self.buffer) {
+            Ok(0) => None,
+            Ok(_) => {
+                self.line += 1;
+                let line = self.buffer.strip_suffix('\n').unwrap_or(&self.buffer);
+                let line = line.strip_suffix('\r').unwrap_or(line);
+                let line_number = self.line;
+
+                Some((self.parse)(line).map_err(|e| e.on_line(line_number).into()))
+            }
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+}
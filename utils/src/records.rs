@@ -0,0 +1,61 @@
+/// Group of consecutive non blank lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record<'i> {
+    /// 1-based line number of the first line of the group in the input
+    pub line: usize,
+    /// Lines of the group with their trailing whitespace removed
+    pub lines: Vec<&'i str>,
+}
+
+/// Iterator over the groups of lines separated by blank lines, see [`records`]
+pub struct Records<'i> {
+    lines: std::iter::Enumerate<std::str::Lines<'i>>,
+}
+
+/// Split the input into groups of lines separated by one or more blank lines.
+///
+/// Lines containing only whitespace count as blank, trailing whitespace is trimmed from every line
+/// so `\r\n` line endings behave the same as `\n`.
+///
+/// # Examples
+///
+/// ```
+/// let input = "a b\r\nc\r\n  \r\n\r\nd\n";
+/// let records: Vec<_> = utils::records(input).collect();
+///
+/// assert_eq!(records.len(), 2);
+/// assert_eq!(records[0].line, 1);
+/// assert_eq!(records[0].lines, vec!["a b", "c"]);
+/// assert_eq!(records[1].line, 5);
+/// assert_eq!(records[1].lines, vec!["d"]);
+/// ```
+pub fn records(input: &str) -> Records<'_> {
+    Records {
+        lines: input.lines().enumerate(),
+    }
+}
+
+impl<'i> Iterator for Records<'i> {
+    type Item = Record<'i>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, first) = self
+            .lines
+            .by_ref()
+            .map(|(index, line)| (index, line.trim_end()))
+            .find(|(_, line)| !line.is_empty())?;
+
+        let mut lines = vec![first];
+        lines.extend(
+            self.lines
+                .by_ref()
+                .map(|(_, line)| line.trim_end())
+                .take_while(|line| !line.is_empty()),
+        );
+
+        Some(Record {
+            line: index + 1,
+            lines,
+        })
+    }
+}
@@ -0,0 +1,54 @@
+use std::fmt::Display;
+
+/// Error shared by every solution, any day specific error converts into it with `?`
+pub type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// A daily puzzle split into a parsing phase and the two challenges that run on its result.
+///
+/// # Examples
+///
+/// ```
+/// use utils::{Error, Solution};
+///
+/// struct Sum;
+///
+/// impl Solution for Sum {
+///     type Input<'i> = Vec<u32>;
+///     type Answer1 = u32;
+///     type Answer2 = usize;
+///
+///     fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, Error> {
+///         Ok(input.lines().map(str::parse).collect::<Result<_, _>>()?)
+///     }
+///
+///     fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, Error> {
+///         Ok(input.iter().sum())
+///     }
+///
+///     fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, Error> {
+///         Ok(input.len())
+///     }
+/// }
+///
+/// let input = Sum.parse("1\n2\n3").unwrap();
+/// assert_eq!(Sum.part1(&input).unwrap(), 6);
+/// assert_eq!(Sum.part2(&input).unwrap(), 3);
+/// assert!(Sum.parse("1\nx").is_err());
+/// ```
+pub trait Solution {
+    /// Parsed puzzle input, it may borrow from the raw text
+    type Input<'i>;
+    /// Answer to the first challenge
+    type Answer1: Display;
+    /// Answer to the second challenge
+    type Answer2: Display;
+
+    /// Parse the raw puzzle input into the representation used by both challenges
+    fn parse<'i>(&self, input: &'i str) -> Result<Self::Input<'i>, Error>;
+
+    /// Solve the first challenge
+    fn part1(&self, input: &Self::Input<'_>) -> Result<Self::Answer1, Error>;
+
+    /// Solve the second challenge
+    fn part2(&self, input: &Self::Input<'_>) -> Result<Self::Answer2, Error>;
+}